@@ -0,0 +1,95 @@
+use macroquad::prelude::*;
+
+/// What is drawn behind the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    /// A solid color.
+    Color(Color),
+    /// An image `<name>.png` from the assets directory, either repeated at its own size or stretched over the whole screen.
+    Image {
+        /// The file name of the image without its extension.
+        name: String,
+        /// Wether the image is repeated instead of stretched.
+        tiled: bool,
+    },
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color(BLACK)
+    }
+}
+
+impl Background {
+    /// Loads the texture of an image background from the assets directory.
+    /// Returns `None` for solid color backgrounds and images that cannot be loaded, which are reported.
+    pub fn load(&self, assets_dir: &str) -> Option<Texture2D> {
+        let Background::Image { name, .. } = self else {
+            return None;
+        };
+        let path = std::path::Path::new(assets_dir).join(format!("{name}.png"));
+        match std::fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                Image::from_file_with_format(&bytes, Some(ImageFormat::Png))
+                    .map_err(|err| err.to_string())
+            }) {
+            Ok(image) => Some(Texture2D::from_image(&image)),
+            Err(err) => {
+                eprintln!(
+                    "Could not load background `{name}` from {}: {err}. Using black instead.",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Clears the screen to the background, using the loaded `texture` of image backgrounds.
+    /// Image backgrounds without a texture are drawn black.
+    pub fn draw(&self, texture: Option<&Texture2D>) {
+        let color = match self {
+            Background::Color(color) => *color,
+            Background::Image { .. } => BLACK,
+        };
+        clear_background(color);
+
+        let (Background::Image { tiled, .. }, Some(texture)) = (self, texture) else {
+            return;
+        };
+        set_default_camera();
+        let (w, h) = (screen_width(), screen_height());
+        if *tiled {
+            let (tile_w, tile_h) = (texture.width(), texture.height());
+            for x in 0..(w / tile_w).ceil() as usize {
+                for y in 0..(h / tile_h).ceil() as usize {
+                    draw_texture(texture, x as f32 * tile_w, y as f32 * tile_h, WHITE);
+                }
+            }
+        } else {
+            draw_texture_ex(
+                texture,
+                0.,
+                0.,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(Vec2::new(w, h)),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+impl std::fmt::Display for Background {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Background::Color(color) => {
+                write!(f, "{},{},{},{}", color.r, color.g, color.b, color.a)
+            }
+            Background::Image { name, tiled } => {
+                write!(f, "{name} {}", if *tiled { "tiled" } else { "stretched" })
+            }
+        }
+    }
+}
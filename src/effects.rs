@@ -0,0 +1,47 @@
+use macroquad::prelude::*;
+
+/// A short-lived expanding ring marking where a collision happened. Purely cosmetic.
+#[derive(Debug, Clone)]
+pub struct ImpactFlash {
+    /// The world position of the collision.
+    position: Vec2,
+    /// The simulated time since the collision.
+    age: f32,
+}
+
+impl ImpactFlash {
+    /// The simulated time a flash is visible for.
+    const DURATION: f32 = 20.;
+    /// The radius the ring expands to before vanishing.
+    const MAX_RADIUS: f32 = 24.;
+
+    /// Creates a new flash at the given world position.
+    pub fn new(position: Vec2) -> Self {
+        Self { position, age: 0. }
+    }
+
+    /// Ages the flash by `dt` and returns wether it is still visible.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.age += dt;
+        self.age < Self::DURATION
+    }
+
+    /// Draws the flash as a fading ring that grows over its lifetime.
+    pub fn draw(&self) {
+        let progress = (self.age / Self::DURATION).min(1.);
+        let color = Color::new(1., 0.9, 0.6, 1. - progress);
+        draw_circle(
+            self.position.x,
+            self.position.y,
+            4. * (1. - progress),
+            color,
+        );
+        draw_circle_lines(
+            self.position.x,
+            self.position.y,
+            Self::MAX_RADIUS * progress,
+            2.,
+            color,
+        );
+    }
+}
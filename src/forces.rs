@@ -0,0 +1,190 @@
+use std::any::Any;
+
+use macroquad::prelude::*;
+
+use crate::space_object::SpaceObject;
+
+/// A law describing the force one object exerts on another. The forces of all active models are summed.
+pub trait ForceModel {
+    /// The force `attractor` exerts on `object`, given the non-zero distance vector pointing from `object` to `attractor`.
+    fn force(&self, object: &SpaceObject, attractor: &SpaceObject, dist: Vec2) -> Vec2;
+
+    /// The model itself, so its parameters can be found and changed in the list of models.
+    fn as_any(&self) -> &dyn Any;
+
+    /// The model itself, so its parameters can be found and changed in the list of models.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Which gravity groups attract each other. By default every group attracts every other, giving full n-body gravity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GravityMatrix {
+    /// Bit `j` of row `i` is set if objects of group `j` attract objects of group `i`.
+    rows: [u32; Self::GROUPS],
+}
+
+impl Default for GravityMatrix {
+    fn default() -> Self {
+        Self {
+            rows: [u32::MAX; Self::GROUPS],
+        }
+    }
+}
+
+impl GravityMatrix {
+    /// The amount of gravity groups.
+    pub const GROUPS: usize = 32;
+
+    /// Sets wether the two groups attract each other, in both directions.
+    pub fn set(&mut self, a: u8, b: u8, attract: bool) {
+        for (row, column) in [(a, b), (b, a)] {
+            if attract {
+                self.rows[row as usize] |= 1 << column;
+            } else {
+                self.rows[row as usize] &= !(1 << column);
+            }
+        }
+    }
+
+    /// Wether objects of the group `attractor` attract objects of the group `object`.
+    pub fn attracts(&self, object: u8, attractor: u8) -> bool {
+        self.rows[object as usize] & (1 << attractor) != 0
+    }
+
+    /// All pairs of groups `(a, b)` with `a <= b` that do not attract each other.
+    pub fn ignored_pairs(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (0..Self::GROUPS as u8)
+            .flat_map(|a| (a..Self::GROUPS as u8).map(move |b| (a, b)))
+            .filter(|&(a, b)| !self.attracts(a, b))
+    }
+}
+
+/// Gravity attracting every object towards every other, proportional to both masses.
+#[derive(Debug, Clone, Copy)]
+pub struct Gravity {
+    /// Wether gravity is applied, switched off to leave only the other forces.
+    pub enabled: bool,
+    /// The gravitic constant.
+    pub constant: f32,
+    /// The power of the distance the force falls off with. 2 reproduces Newtonian gravity.
+    pub exponent: f32,
+    /// The distance per square root of an attractor's mass beyond which it exerts no gravity.
+    pub cutoff: f32,
+    /// The mass below which objects exert no gravity.
+    pub min_mass: f32,
+    /// Which gravity groups attract each other.
+    pub groups: GravityMatrix,
+}
+
+impl ForceModel for Gravity {
+    fn force(&self, object: &SpaceObject, attractor: &SpaceObject, dist: Vec2) -> Vec2 {
+        // Skip negligible gravity of light or distant attractors.
+        // The cutoff scales with the root of the attractor's mass, so all skipped accelerations are below the same threshold.
+        if !self.enabled
+            || !self
+                .groups
+                .attracts(object.get_gravity_group(), attractor.get_gravity_group())
+            || attractor.get_mass() < self.min_mass
+            || dist.length() > self.cutoff * attractor.get_mass().sqrt()
+        {
+            return Vec2::ZERO;
+        }
+        // The gravitational force between the two is in the direction of the distance vector, proportional to their masses and inversely proportional to the distance vectors length raised to the falloff exponent.
+        // Raising the squared length to half the exponent keeps p = 2 exactly equal to the inverse-square law, as powf(x, 1) is x.
+        dist.normalize() * self.constant * object.get_mass() * attractor.get_mass()
+            / dist.length_squared().powf(self.exponent / 2.)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The electric force between charged objects.
+#[derive(Debug, Clone, Copy)]
+pub struct Electric {
+    /// The constant of the force, scaling the product of both charges.
+    pub constant: f32,
+}
+
+impl ForceModel for Electric {
+    fn force(&self, object: &SpaceObject, attractor: &SpaceObject, dist: Vec2) -> Vec2 {
+        // Most objects are uncharged, so the common gravity-only case skips the rest of the force
+        if object.get_charge() == 0. || attractor.get_charge() == 0. {
+            return Vec2::ZERO;
+        }
+        // Like charges repel and opposite charges attract, inversely proportional to the square of the distance.
+        -dist.normalize() * self.constant * object.get_charge() * attractor.get_charge()
+            / dist.length_squared()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The force models acting between every pair of objects, holding the only copy of their parameters.
+pub struct Forces {
+    /// The active models, always including gravity and the electric force.
+    models: Vec<Box<dyn ForceModel>>,
+}
+
+impl Forces {
+    /// Creates the list of force models from gravity and the electric force.
+    pub fn new(gravity: Gravity, electric: Electric) -> Self {
+        Self {
+            models: vec![Box::new(gravity), Box::new(electric)],
+        }
+    }
+
+    /// The sum of the forces of all models `attractor` exerts on `object`, given the non-zero distance vector pointing from `object` to `attractor`.
+    pub fn force(&self, object: &SpaceObject, attractor: &SpaceObject, dist: Vec2) -> Vec2 {
+        self.models.iter().fold(Vec2::ZERO, |f, model| {
+            f + model.force(object, attractor, dist)
+        })
+    }
+
+    /// The gravity model.
+    pub fn gravity(&self) -> &Gravity {
+        self.model().expect("gravity is always a force model")
+    }
+
+    /// The gravity model, to change its parameters.
+    pub fn gravity_mut(&mut self) -> &mut Gravity {
+        self.model_mut().expect("gravity is always a force model")
+    }
+
+    /// The electric force model.
+    pub fn electric(&self) -> &Electric {
+        self.model()
+            .expect("the electric force is always a force model")
+    }
+
+    /// The electric force model, to change its parameters.
+    pub fn electric_mut(&mut self) -> &mut Electric {
+        self.model_mut()
+            .expect("the electric force is always a force model")
+    }
+
+    /// The first model of type `T`, if any.
+    fn model<T: ForceModel + 'static>(&self) -> Option<&T> {
+        self.models
+            .iter()
+            .find_map(|model| model.as_any().downcast_ref())
+    }
+
+    /// The first model of type `T`, if any, to change its parameters.
+    fn model_mut<T: ForceModel + 'static>(&mut self) -> Option<&mut T> {
+        self.models
+            .iter_mut()
+            .find_map(|model| model.as_any_mut().downcast_mut())
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::space_object::{ObjectState, SpaceObject};
+
+/// The state of the simulation after one physics step.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    /// The simulated time elapsed since the scenario started.
+    sim_time: f64,
+    /// The simulated time of the last collision of recently collided pairs, keyed by their ids in ascending order.
+    contacts: HashMap<(u64, u64), f64>,
+    /// The state of every object.
+    states: Vec<ObjectState>,
+}
+
+/// The objects and simulation clock restored by rewinding.
+pub struct Rewound {
+    /// The objects as they were at the snapshot.
+    pub objects: Vec<SpaceObject>,
+    /// The simulated time elapsed since the scenario started.
+    pub sim_time: f64,
+    /// The simulated time of the last collision of recently collided pairs, keyed by their ids in ascending order.
+    pub contacts: HashMap<(u64, u64), f64>,
+}
+
+/// A ring buffer of the most recent physics states, allowing to rewind the simulation.
+///
+/// Every snapshot stores one [`ObjectState`] of about 140 bytes per object, so a full buffer costs roughly `140 * capacity` bytes per simulated object.
+/// The simulated time and the collision cooldowns are stored along with it, so rewinding does not leave the clock running ahead.
+/// In addition, one full copy of every object appearing in the buffer is kept to restore the parts of objects that never change, such as sprites and labels.
+#[derive(Debug)]
+pub struct History {
+    /// The stored snapshots, oldest first.
+    snapshots: VecDeque<Snapshot>,
+    /// The maximum amount of stored snapshots.
+    capacity: usize,
+    /// A copy of every object appearing in the buffer by id, with the number of the last snapshot it appeared in.
+    templates: HashMap<u64, (SpaceObject, u64)>,
+    /// The total amount of snapshots ever pushed.
+    pushed: u64,
+    /// The index of the snapshot currently restored while rewinding, if any.
+    cursor: Option<usize>,
+}
+
+impl History {
+    /// Creates an empty history storing up to `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            templates: HashMap::new(),
+            pushed: 0,
+            cursor: None,
+        }
+    }
+
+    /// Wether the simulation is currently rewound to a stored snapshot.
+    pub fn is_rewound(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Removes all stored snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.templates.clear();
+        self.cursor = None;
+    }
+
+    /// Stores the current state of all objects along with the simulated time and collision cooldowns, forgetting the oldest snapshot if the buffer is full.
+    pub fn push(
+        &mut self,
+        objects: &[SpaceObject],
+        sim_time: f64,
+        contacts: &HashMap<(u64, u64), f64>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(Snapshot {
+            sim_time,
+            contacts: contacts.clone(),
+            states: objects.iter().map(SpaceObject::state).collect(),
+        });
+        self.pushed += 1;
+
+        for object in objects {
+            self.templates
+                .entry(object.get_id())
+                .and_modify(|(_, last_seen)| *last_seen = self.pushed)
+                .or_insert_with(|| (object.clone(), self.pushed));
+        }
+        // Objects that last appeared before the oldest stored snapshot can never be restored again
+        let oldest = self.pushed - self.snapshots.len() as u64;
+        self.templates
+            .retain(|_, (_, last_seen)| *last_seen > oldest);
+    }
+
+    /// Steps one snapshot further into the past and returns the objects and simulation clock as they were at that point.
+    /// Returns `None` if there is no older snapshot.
+    pub fn rewind(&mut self) -> Option<Rewound> {
+        let index = match self.cursor {
+            Some(0) => return None,
+            Some(index) => index - 1,
+            None => self.snapshots.len().checked_sub(1)?,
+        };
+        self.cursor = Some(index);
+
+        let snapshot = &self.snapshots[index];
+        Some(Rewound {
+            objects: snapshot
+                .states
+                .iter()
+                .filter_map(|state| {
+                    let (template, _) = self.templates.get(&state.id)?;
+                    let mut object = template.clone();
+                    object.restore(state);
+                    Some(object)
+                })
+                .collect(),
+            sim_time: snapshot.sim_time,
+            contacts: snapshot.contacts.clone(),
+        })
+    }
+
+    /// Continues live physics from the currently rewound snapshot, discarding all snapshots after it.
+    pub fn resume(&mut self) {
+        if let Some(index) = self.cursor.take() {
+            let discarded = self.snapshots.len() - index - 1;
+            self.snapshots.truncate(index + 1);
+            self.pushed -= discarded as u64;
+            // Templates only seen in the discarded future still carry their later snapshot numbers, clamp them to the present
+            for (_, last_seen) in self.templates.values_mut() {
+                *last_seen = (*last_seen).min(self.pushed);
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use macroquad::{
+    miniquad::{BlendFactor, BlendState, BlendValue, Equation},
+    prelude::*,
+};
+
+/// A post-processing pass rendering the world into a texture and drawing it through a shader that bends it radially around massive bodies, like light bent by their gravity.
+/// If the shader cannot be compiled, the texture is drawn undistorted.
+pub struct Lensing {
+    /// The texture the world is rendered into, recreated when the screen size changes.
+    target: RenderTarget,
+    /// The width and height of the target in pixels.
+    size: (u32, u32),
+    /// The distortion shader, unless it failed to compile.
+    material: Option<Material>,
+}
+
+/// A single body bending the rendered world around it.
+#[derive(Debug, Clone, Copy)]
+pub struct Lens {
+    /// The position of the body on screen in pixels.
+    pub center: Vec2,
+    /// The radius of the body on screen in pixels.
+    pub radius: f32,
+}
+
+impl Lensing {
+    /// The maximum amount of bodies bending the world at once.
+    pub const MAX_LENSES: usize = 4;
+    /// The fraction of a lens's radius the world is shifted by at its edge, falling off with the distance beyond it.
+    const STRENGTH: f32 = 0.4;
+    /// The names of the lens uniforms, one per supported lens.
+    const LENS_UNIFORMS: [&'static str; Self::MAX_LENSES] = ["Lens0", "Lens1", "Lens2", "Lens3"];
+
+    /// Creates the render target and compiles the distortion shader, reporting if compilation fails.
+    pub fn new() -> Self {
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: VERTEX_SHADER,
+                fragment: FRAGMENT_SHADER,
+            },
+            MaterialParams {
+                pipeline_params: PipelineParams {
+                    // The world is drawn over the background, so keep the transparency of empty space
+                    color_blend: Some(BlendState::new(
+                        Equation::Add,
+                        BlendFactor::Value(BlendValue::SourceAlpha),
+                        BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                    )),
+                    ..Default::default()
+                },
+                uniforms: Self::LENS_UNIFORMS
+                    .iter()
+                    .map(|name| (name.to_string(), UniformType::Float4))
+                    .chain([("Aspect".to_owned(), UniformType::Float1)])
+                    .collect(),
+                ..Default::default()
+            },
+        )
+        .map_err(|err| {
+            eprintln!("Could not compile lensing shader: {err}. Drawing without distortion.")
+        })
+        .ok();
+
+        let size = Self::screen_size();
+        Self {
+            target: render_target(size.0, size.1),
+            size,
+            material,
+        }
+    }
+
+    /// The current screen size in whole pixels, at least one each.
+    fn screen_size() -> (u32, u32) {
+        (
+            (screen_width() as u32).max(1),
+            (screen_height() as u32).max(1),
+        )
+    }
+
+    /// Returns the target the world is to be rendered into this frame, matching the screen size.
+    pub fn target(&mut self) -> RenderTarget {
+        let size = Self::screen_size();
+        if size != self.size {
+            self.target.delete();
+            self.target = render_target(size.0, size.1);
+            self.size = size;
+        }
+        self.target.clone()
+    }
+
+    /// Draws the rendered world to the whole screen, bent around up to [`Self::MAX_LENSES`] of the given lenses.
+    pub fn draw(&self, lenses: &[Lens]) {
+        set_default_camera();
+        let (w, h) = (screen_width(), screen_height());
+
+        if let Some(material) = &self.material {
+            for (i, name) in Self::LENS_UNIFORMS.iter().enumerate() {
+                // Lenses are given in texture coordinates, with the radius relative to the screen width
+                let uniform = lenses.get(i).map_or(Vec4::ZERO, |lens| {
+                    vec4(
+                        lens.center.x / w,
+                        lens.center.y / h,
+                        lens.radius / w,
+                        Self::STRENGTH,
+                    )
+                });
+                material.set_uniform(name, uniform);
+            }
+            material.set_uniform("Aspect", h / w);
+            gl_use_material(material);
+        }
+        draw_texture_ex(
+            &self.target.texture,
+            0.,
+            0.,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(w, h)),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+}
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying mediump vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 100
+precision mediump float;
+
+varying mediump vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+// Center in texture coordinates, radius relative to the width and strength of every lens, a radius of zero disables it
+uniform vec4 Lens0;
+uniform vec4 Lens1;
+uniform vec4 Lens2;
+uniform vec4 Lens3;
+// Height divided by width, to keep lenses circular
+uniform float Aspect;
+
+// The shift towards the lens center, strongest at the edge of the lens and falling off with distance beyond it
+vec2 bend(vec4 lens) {
+    vec2 scale = vec2(1.0, Aspect);
+    vec2 d = (uv - lens.xy) * scale;
+    float r = length(d);
+    if (lens.z <= 0.0 || r <= 0.0) {
+        return vec2(0.0);
+    }
+    float shift = lens.w * lens.z * lens.z / max(r, lens.z);
+    return -d / r * shift / scale;
+}
+
+void main() {
+    vec2 offset = bend(Lens0) + bend(Lens1) + bend(Lens2) + bend(Lens3);
+    gl_FragColor = texture2D(Texture, uv + offset) * color;
+}
+";
@@ -1,93 +1,902 @@
+use std::collections::HashMap;
+
 use macroquad::prelude::*;
 
+mod settings;
+use settings::Settings;
 mod space_object;
-use space_object::SpaceObject;
+use space_object::{CollisionRules, Durability, FirePattern, SpaceObject};
+mod background;
+use background::Background;
+mod effects;
+use effects::ImpactFlash;
+mod forces;
+use forces::{Electric, ForceModel, Forces, Gravity, GravityMatrix};
+mod history;
+mod lensing;
+use history::History;
+use lensing::{Lens, Lensing};
+mod menu;
+mod recorder;
+use recorder::Recorder;
+mod orbit;
+mod tuning;
+use menu::{MenuAction, PauseMenu};
+use orbit::{circular_velocity, OrbitClass, OrbitalElements};
+use tuning::{Parameter, TuningPanel};
+mod scenario;
+use scenario::{
+    ObjectKind, ObjectSpec, Scenario, DEFAULT_ASSIST_KEYS, DEFAULT_KEYMAPS, DEFAULT_TINTS,
+};
+mod sprites;
+use sprites::{SpriteKey, SpriteTextures};
 
-#[macroquad::main("Orbits")]
-async fn main() {
-    let mut instance = OrbitsInstance::new().unwrap();
+fn main() {
+    // The settings need to be known before the window is opened
+    let mut settings = Settings::load(Settings::PATH);
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--record") {
+        settings.record = true;
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "--players") {
+        match args.get(index + 1).map(|count| count.parse()) {
+            Some(Ok(count)) => settings.players = Some(count),
+            _ => eprintln!("Ignoring --players, expected a player count after it"),
+        }
+    }
+    macroquad::Window::from_config(settings.window_conf(), run(settings));
+}
+
+/// Runs the simulation in the opened window.
+async fn run(settings: Settings) {
+    let mut instance = OrbitsInstance::new(&settings).unwrap();
+    // Real time not yet simulated by physics ticks
+    let mut accumulator = 0.;
 
     loop {
         // Read user input and process it
         instance.interact();
-        // Run physics updates
-        instance.update();
-        // Draw the game to the frame
-        instance.draw();
+        // Run physics updates at a fixed rate, independent of the frame rate
+        accumulator = if instance.paused || instance.history.is_rewound() {
+            0.
+        } else {
+            (accumulator + get_frame_time()).min(OrbitsInstance::MAX_FRAME_TIME)
+        };
+        // Steps may span more or less than a tick when the timestep is adaptive
+        let mut step = instance.step_size();
+        while accumulator >= step * OrbitsInstance::TICK {
+            instance.update(step);
+            accumulator -= step * OrbitsInstance::TICK;
+            step = instance.step_size();
+        }
+        // Draw the game to the frame, interpolating between the last two physics steps
+        instance.draw((accumulator / (step * OrbitsInstance::TICK)).min(1.));
+
+        if instance.quit_requested {
+            instance.stop_recording();
+            break;
+        }
 
         next_frame().await
     }
 }
 
+/// Describes what happens to objects leaving the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    /// Objects other than ships are removed once they are too far from the origin.
+    Remove,
+    /// The arena is a torus, objects leaving it on one side re-enter on the opposite side.
+    Wrap,
+}
+
+/// Describes what the single camera frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraMode {
+    /// All ships, centered on the origin.
+    Ships,
+    /// The bounding box of all objects, including bodies and projectiles.
+    FitAll,
+}
+
 /// An instance of the simulation.
 struct OrbitsInstance {
     /// All objects being simulated.
     objects: Vec<SpaceObject>,
+    /// The amount of objects above which the oldest projectiles are removed when new ones are fired.
+    max_objects: usize,
     /// The current camera.
     camera: Camera2D,
-    /// Selection of cached images.
-    image_cache: Vec<Image>,
+    /// The world units per pixel currently shown by the auto-framing camera.
+    camera_scale: f32,
+    /// The world position currently centered by the auto-framing camera.
+    camera_target: Vec2,
+    /// What the single camera frames.
+    camera_mode: CameraMode,
+    /// The remaining real time in seconds the single camera frames all objects regardless of its mode.
+    camera_fit_timer: f32,
+    /// The factor the camera shake on impacts is scaled by, 0 disables it.
+    screen_shake: f32,
+    /// The distance in pixels the camera is shaken by at the start of the current shake.
+    shake_intensity: f32,
+    /// The remaining real time in seconds of the current camera shake.
+    shake_timer: f32,
+    /// The minimum world units per pixel the auto-framing camera zooms in to.
+    min_camera_scale: f32,
+    /// The maximum world units per pixel the auto-framing camera zooms out to.
+    max_camera_scale: f32,
+    /// Selection of cached sprite textures by name.
+    sprites: HashMap<SpriteKey, SpriteTextures>,
+    /// The force models acting between every pair of objects, holding gravity and the electric force with all their parameters.
+    forces: Forces,
+    /// What happens to objects leaving the arena.
+    boundary: Boundary,
+    /// The factor by which simulated time passes relative to frame time.
+    time_scale: f32,
+    /// The factor simulated time passes at outside of slow motion.
+    speed: f32,
+    /// The simulated time elapsed since the scenario started, advanced by every physics step.
+    /// All time-dependent simulation logic reads from this clock instead of the wall clock, keeping runs reproducible.
+    sim_time: f64,
+    /// Wether the simulation automatically slows down on imminent collisions.
+    bullet_time: bool,
+    /// The amount of substeps every physics step is divided into, trading CPU time for accuracy.
+    substeps: usize,
+    /// Wether the length of physics steps adapts to the speed and separation of objects instead of always being one tick.
+    adaptive_timestep: bool,
+    /// The rules used to resolve collisions.
+    collision_rules: CollisionRules,
+    /// The amount of ships placed symmetrically in every scenario instead of its own ships, if any.
+    players: Option<usize>,
+    /// The simulated time after spawning during which ships take no collision damage.
+    spawn_protection: f32,
+    /// The speed no object moves faster than. Infinity disables the limit.
+    max_speed: f32,
+    /// Wether holding its assist key steers a ship towards a circular orbit, as a training aid.
+    orbit_assist: bool,
+    /// Wether objects overlapping when a scenario starts are pushed apart instead of only reported.
+    separate_overlaps: bool,
+    /// Wether celestial bodies are held in place while ships and projectiles keep moving.
+    freeze_bodies: bool,
+    /// The trajectory recording currently being written, if any.
+    recorder: Option<Recorder>,
+    /// The CSV file trajectories are recorded to.
+    record_path: std::path::PathBuf,
+    /// Which objects are written to trajectory recordings.
+    record_filter: recorder::RecordFilter,
+    /// Wether the world is drawn bent around massive bodies, like light passing them.
+    show_lensing: bool,
+    /// The render target and shader of the lensing effect, created when it is first shown.
+    lensing: Option<Lensing>,
+    /// What is drawn behind the simulation.
+    background: Background,
+    /// The loaded image of the background, if it is an image that could be loaded.
+    background_texture: Option<Texture2D>,
+    /// Cosmetic effects marking recent collisions.
+    effects: Vec<ImpactFlash>,
+    /// The time after a collision before the same pair of objects can collide again.
+    collision_cooldown: f32,
+    /// The simulated time of the last collision of recently collided pairs, keyed by their ids in ascending order.
+    contacts: HashMap<(u64, u64), f64>,
+    /// Remaining ticks of the current slow motion window.
+    slow_motion_timer: f32,
+    /// Remaining ticks until a new slow motion window may be triggered.
+    slow_motion_cooldown: f32,
+    /// Wether every ship is drawn in its own pane with a camera following it.
+    split_screen: bool,
+    /// Wether the gravitational field strength is drawn as a heatmap beneath the objects.
+    show_field: bool,
+    /// The amount of heatmap cells along each axis of the visible area.
+    field_resolution: usize,
+    /// Wether celestial bodies are drawn with a shaded gravity well around them.
+    show_wells: bool,
+    /// Wether objects are drawn with their name above them.
+    show_labels: bool,
+    /// Wether the predicted trajectory of every ship under its currently held thrust is drawn.
+    show_prediction: bool,
+    /// The predicted trajectories of all ships by id, computed at most once per physics step and shared by all panes, or `None` once outdated.
+    predictions: Option<HashMap<u64, Vec<Vec<Vec2>>>>,
+    /// Wether objects can be placed with the mouse.
+    sandbox: bool,
+    /// The mass of bodies placed in sandbox mode.
+    sandbox_mass: f32,
+    /// The size of bodies placed in sandbox mode.
+    sandbox_size: f32,
+    /// The world position of the object currently being placed in sandbox mode, if any.
+    pending_placement: Option<Vec2>,
+    /// The id of the object shown in the inspector panel, if any.
+    tracked: Option<u64>,
+    /// Wether a screenshot of the current frame should be saved after drawing it.
+    screenshot_requested: bool,
+    /// A status message, e.g. about the last screenshot, and the remaining frames it is displayed for.
+    status_message: Option<(String, f32)>,
+    /// The recent physics states the simulation can be rewound to.
+    history: History,
+    /// Wether the simulation is paused and the pause menu shown.
+    paused: bool,
+    /// The pause menu.
+    menu: PauseMenu,
+    /// Wether the panel to adjust simulation parameters is shown.
+    show_tuning: bool,
+    /// The panel to adjust simulation parameters.
+    tuning: TuningPanel,
+    /// Wether the user asked to close the application.
+    quit_requested: bool,
+    /// The directory sprites and scenarios are loaded from.
+    assets_dir: String,
+    /// All available scenarios, starting with the built-in default scenario.
+    scenarios: Vec<Scenario>,
+    /// The index of the currently loaded scenario.
+    scenario_index: usize,
+    /// The modification time of the current scenario's file when it was last loaded, to reload it on changes.
+    scenario_modified: Option<std::time::SystemTime>,
+    /// The index of the scenario currently selected in the pause menu.
+    selected_scenario: usize,
 }
 
 impl OrbitsInstance {
-    /// The gravitic constant governing the attraction of space objects to one another
-    const GRAVITY: f32 = 0.1;
+    /// The real time in seconds simulated by a single physics tick.
+    const TICK: f32 = 1. / 60.;
+    /// The maximum real time in seconds caught up on per frame, so slow frames do not cause ever more ticks.
+    const MAX_FRAME_TIME: f32 = 0.25;
+    /// The default gravitic constant governing the attraction of space objects to one another
+    const DEFAULT_GRAVITY: f32 = 0.1;
+    /// The default constant governing the force between charged objects.
+    const DEFAULT_COULOMB: f32 = 1.0;
+    /// The default fraction of their speed projectiles keep when ricocheting off bodies.
+    const DEFAULT_RESTITUTION: f32 = 0.8;
+    /// The factor the gravitic constant is multiplied or divided by when adjusted.
+    const GRAVITY_STEP: f32 = 1.2;
+    /// The default power of the distance gravity falls off with, as in Newtonian gravity.
+    const DEFAULT_GRAVITY_EXPONENT: f32 = 2.0;
+    /// Half the side length of the square arena.
+    const ARENA_SIZE: f32 = 1000.;
+    /// The fraction of the smallest object's size an object may move per step before the step is divided further.
+    const SUBSTEP_FRACTION: f32 = 0.5;
+    /// The maximum amount of steps a substep is divided into for fast objects.
+    const MAX_SUBSTEPS: usize = 64;
+    /// The time scale the simulation slows down to during bullet time.
+    const SLOW_MOTION_SCALE: f32 = 0.25;
+    /// The amount of ticks a slow motion window lasts.
+    const SLOW_MOTION_DURATION: f32 = 60.;
+    /// The amount of ticks after a slow motion window before another one can be triggered.
+    const SLOW_MOTION_COOLDOWN: f32 = 180.;
+    /// The amount of ticks the near-collision detector looks into the future.
+    const SLOW_MOTION_LOOKAHEAD: f32 = 20.;
+    /// The fraction by which the time scale approaches its target each tick.
+    const TIME_SCALE_LERP: f32 = 0.1;
+    /// The fraction of the closest pair's separation the fastest object may cover in one adaptive step.
+    const ADAPTIVE_PRECISION: f32 = 0.02;
+    /// The maximum amount of substeps selectable for every physics step.
+    const MAX_QUALITY_SUBSTEPS: usize = 8;
+    /// The shortest adaptive step, in ticks.
+    const MIN_STEP: f32 = 0.125;
+    /// The longest adaptive step, in ticks.
+    const MAX_STEP: f32 = 4.0;
+    /// The minimum amount of heatmap cells along each axis.
+    const MIN_FIELD_RESOLUTION: usize = 4;
+    /// The maximum amount of heatmap cells along each axis.
+    const MAX_FIELD_RESOLUTION: usize = 128;
+    /// The fraction by which the auto-framing camera approaches its target scale each frame.
+    const CAMERA_SMOOTHING: f32 = 0.1;
+    /// The real time in seconds a one-shot fit keeps all objects framed.
+    const CAMERA_FIT_DURATION: f32 = 3.;
+    /// The maximum change of velocity per tick the orbit assist applies to a ship.
+    const ORBIT_ASSIST_ACCELERATION: f32 = 0.005;
+    /// The mass above which bodies bend the world around them while lensing is shown.
+    const LENS_MIN_MASS: f32 = 256.;
+    /// The real time in seconds a camera shake takes to decay.
+    const SHAKE_DURATION: f32 = 0.4;
+    /// The distance in pixels the camera is shaken by for any impact, so even light hits are felt.
+    const BASE_SHAKE: f32 = 4.;
+    /// The distance in pixels the camera is shaken by per factor of e the impact energy grows by.
+    /// The logarithm keeps shakes of impacts from projectiles to planets within a useful range.
+    const SHAKE_PER_ENERGY: f32 = 4.;
+    /// The largest distance in pixels the camera is shaken by for impacts on ships, before scaling by the setting.
+    const MAX_SHIP_SHAKE: f32 = 8.;
+    /// The largest distance in pixels the camera is shaken by, reached only by crashing bodies, before scaling by the setting.
+    const MAX_SHAKE: f32 = 16.;
+    /// The seed of the random shake directions, reset with every scenario so shakes repeat exactly.
+    const SHAKE_SEED: u64 = 0x0b17;
+    /// The largest amount of passes pushing overlapping objects apart at the start of a scenario.
+    const SEPARATION_PASSES: usize = 32;
+    /// The gap left between objects pushed apart at the start of a scenario.
+    const SEPARATION_MARGIN: f32 = 1.;
+    /// The distance from their primary at which players are placed by `spawn_players`.
+    const PLAYER_RING_RADIUS: f32 = 256.;
+    /// The world units per pixel shown by the cameras in split-screen mode.
+    const SPLIT_SCREEN_SCALE: f32 = 1.0;
+    /// The initial velocity of a placed object per world unit dragged in sandbox mode.
+    const SANDBOX_VELOCITY_SCALE: f32 = 0.01;
+    /// The factor mass or size of placed objects changes by per scroll step in sandbox mode.
+    const SANDBOX_SCROLL_STEP: f32 = 1.2;
+    /// The factor by which the strongest attractor's pull must exceed the second strongest to count as a dominant primary.
+    const DOMINANCE_RATIO: f32 = 4.0;
+    /// The amount of steps the trajectory of ships is predicted for.
+    const PREDICTION_STEPS: usize = 600;
+    /// The simulated time covered by every step of the trajectory prediction.
+    const PREDICTION_STEP: f32 = 1.0;
+    /// The amount of frames a status message is displayed for.
+    const STATUS_MESSAGE_DURATION: f32 = 120.;
 
     /// Creates a new instance of the simulation
-    fn new() -> Result<Self, macroquad::Error> {
-        let image_cache = vec![
-            Image::from_file_with_format(
-                include_bytes!("../assets/ship.png"),
-                Some(ImageFormat::Png),
-            )?,
-            Image::from_file_with_format(
-                include_bytes!("../assets/ship_power.png"),
-                Some(ImageFormat::Png),
-            )?,
-            Image::from_file_with_format(
-                include_bytes!("../assets/projectile.png"),
-                Some(ImageFormat::Png),
-            )?,
-            Image::from_file_with_format(
-                include_bytes!("../assets/sun.png"),
-                Some(ImageFormat::Png),
-            )?,
-            Image::from_file_with_format(
-                include_bytes!("../assets/earth.png"),
-                Some(ImageFormat::Png),
-            )?,
-        ];
-        Ok(OrbitsInstance {
-            objects: vec![
-                // Ships
-                SpaceObject::ship(
-                    Vec2::new(256.0, 0.0),
-                    Vec2::new(0.0, 0.6),
-                    &image_cache[0],
-                    [KeyCode::W, KeyCode::A, KeyCode::D, KeyCode::S],
-                ),
-                SpaceObject::ship(
-                    Vec2::new(-256.0, 0.0),
-                    Vec2::new(0.0, -0.6),
-                    &image_cache[0],
-                    [KeyCode::I, KeyCode::J, KeyCode::L, KeyCode::K],
-                ),
-                // Sun
-                SpaceObject::body(
-                    Vec2::new(0.0, 0.0),
-                    Vec2::new(0.0, 0.0),
-                    1024.,
-                    96.,
-                    &image_cache[3],
-                ),
-            ],
+    fn new(settings: &Settings) -> Result<Self, macroquad::Error> {
+        let sprites = sprites::load_sprites(&settings.assets_dir)?;
+        let mut scenarios = vec![Scenario::default()];
+        scenarios.extend(Scenario::load_all(&settings.assets_dir));
+
+        let mut instance = OrbitsInstance {
+            objects: Vec::new(),
+            max_objects: settings.max_objects,
             camera: Camera2D::default(),
-            image_cache,
-        })
+            camera_scale: settings.min_camera_scale,
+            camera_target: Vec2::ZERO,
+            camera_mode: CameraMode::Ships,
+            camera_fit_timer: 0.,
+            screen_shake: settings.screen_shake,
+            shake_intensity: 0.,
+            shake_timer: 0.,
+            min_camera_scale: settings.min_camera_scale,
+            max_camera_scale: settings.max_camera_scale.max(settings.min_camera_scale),
+            sprites,
+            forces: Forces::new(
+                Gravity {
+                    enabled: true,
+                    constant: Self::DEFAULT_GRAVITY,
+                    exponent: Self::DEFAULT_GRAVITY_EXPONENT,
+                    cutoff: settings.gravity_cutoff,
+                    min_mass: settings.gravity_min_mass,
+                    groups: GravityMatrix::default(),
+                },
+                Electric {
+                    constant: Self::DEFAULT_COULOMB,
+                },
+            ),
+            boundary: Boundary::Remove,
+            time_scale: 1.0,
+            speed: 1.0,
+            sim_time: 0.0,
+            bullet_time: true,
+            adaptive_timestep: settings.adaptive_timestep,
+            substeps: settings.substeps.clamp(1, Self::MAX_QUALITY_SUBSTEPS),
+            collision_rules: CollisionRules {
+                bounce_off_bodies: false,
+                restitution: Self::DEFAULT_RESTITUTION,
+                ship_bounce: false,
+                ship_ram_damage: settings.ship_ram_damage,
+                friendly_fire: false,
+                disabled: false,
+                invincible_ships: false,
+            },
+            collision_cooldown: settings.collision_cooldown,
+            contacts: HashMap::new(),
+            show_lensing: false,
+            lensing: None,
+            background: Background::default(),
+            background_texture: None,
+            effects: Vec::new(),
+            players: settings.players,
+            spawn_protection: settings.spawn_protection,
+            max_speed: settings.max_speed,
+            orbit_assist: settings.orbit_assist,
+            separate_overlaps: settings.separate_overlaps,
+            freeze_bodies: false,
+            recorder: None,
+            record_path: settings.record_path.clone().into(),
+            record_filter: settings.record_filter,
+            slow_motion_timer: 0.0,
+            slow_motion_cooldown: 0.0,
+            split_screen: false,
+            show_field: false,
+            field_resolution: 32,
+            show_wells: false,
+            show_labels: true,
+            show_prediction: true,
+            predictions: None,
+            sandbox: false,
+            sandbox_mass: 16.,
+            sandbox_size: 24.,
+            pending_placement: None,
+            tracked: None,
+            screenshot_requested: false,
+            status_message: None,
+            history: History::new(settings.history_length),
+            paused: false,
+            menu: PauseMenu::default(),
+            show_tuning: false,
+            tuning: TuningPanel::default(),
+            quit_requested: false,
+            assets_dir: settings.assets_dir.clone(),
+            scenarios,
+            scenario_index: 0,
+            scenario_modified: None,
+            selected_scenario: 0,
+        };
+        instance.restart();
+        if settings.record {
+            instance.start_recording();
+        }
+
+        Ok(instance)
+    }
+
+    /// Resets the simulation to the scenario at `index` of the available scenarios, keeping the window and caches.
+    /// If the scenario cannot be built, the current simulation is kept.
+    fn load_scenario(&mut self, index: usize) -> Result<(), String> {
+        // Everything that can fail happens before the current simulation is touched
+        let scenario = &self.scenarios[index];
+        let mut objects = scenario.build(&self.sprites)?;
+        // The scenario brings its own constants and gravity groups, tuned parameters are kept
+        let forces = Forces::new(
+            Gravity {
+                constant: scenario.gravity.unwrap_or(Self::DEFAULT_GRAVITY),
+                groups: scenario.gravity_matrix,
+                ..*self.forces.gravity()
+            },
+            Electric {
+                constant: scenario.coulomb.unwrap_or(Self::DEFAULT_COULOMB),
+            },
+        );
+        if let Some(players) = self.players.or(scenario.players) {
+            self.spawn_players(&mut objects, players, &forces)?;
+        }
+        let background = scenario.background.clone().unwrap_or_default();
+        let modified = scenario.path.as_deref().and_then(modification_time);
+
+        self.objects = objects;
+        self.forces = forces;
+        self.check_overlaps(index);
+        for object in self.objects.iter_mut() {
+            object.protect(self.spawn_protection);
+        }
+        self.scenario_index = index;
+        self.scenario_modified = modified;
+        // Image backgrounds that fail to load are drawn black instead
+        if background != self.background || self.background_texture.is_none() {
+            self.background_texture = background.load(&self.assets_dir);
+        }
+        self.background = background;
+        self.tracked = None;
+        self.history.clear();
+        self.predictions = None;
+        self.contacts.clear();
+        self.effects.clear();
+
+        self.time_scale = self.speed;
+        self.sim_time = 0.0;
+        self.slow_motion_timer = 0.0;
+        self.slow_motion_cooldown = 0.0;
+        self.camera_scale = self.min_camera_scale;
+        self.camera_target = Vec2::ZERO;
+        self.shake_intensity = 0.0;
+        self.shake_timer = 0.0;
+        macroquad::rand::srand(Self::SHAKE_SEED);
+        Ok(())
+    }
+
+    /// Reports objects of the scenario at `index` that overlap right away, which would collide on the first step.
+    /// If overlaps are to be separated, they are pushed apart first and only those that remain are reported.
+    fn check_overlaps(&mut self, index: usize) {
+        if self.separate_overlaps {
+            self.separate_overlapping();
+        }
+        let pairs = self.overlapping_pairs();
+        if pairs.is_empty() {
+            return;
+        }
+        let list = pairs
+            .iter()
+            .map(|&(a, b)| {
+                format!(
+                    "{} {a} and {} {b}",
+                    self.objects[a].kind_name(),
+                    self.objects[b].kind_name()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.report(format!(
+            "Scenario {} starts with overlapping objects: {list}",
+            self.scenarios[index].name
+        ));
+    }
+
+    /// The index pairs of all objects overlapping each other that are allowed to collide.
+    fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        // An object can only overlap others within its radius plus that of the largest object
+        let largest = self
+            .objects
+            .iter()
+            .map(|object| object.get_size())
+            .fold(0., f32::max);
+        let mut pairs = Vec::new();
+        for (i, a) in self.objects.iter().enumerate() {
+            for j in self.objects_in_radius(a.get_position(), (a.get_size() + largest) / 2.) {
+                let b = &self.objects[j];
+                if j > i && a.can_collide(b, &self.collision_rules) && a.overlaps(b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Pushes overlapping objects apart along the line between their centers, splitting the distance if both can be moved.
+    /// Pairs of anchored or kinematic objects stay in place, and crowded objects may still overlap after the last pass.
+    fn separate_overlapping(&mut self) {
+        for _ in 0..Self::SEPARATION_PASSES {
+            let pairs = self.overlapping_pairs();
+            if pairs.is_empty() {
+                return;
+            }
+            for (i, j) in pairs {
+                let (a, b) = (&self.objects[i], &self.objects[j]);
+                let offset = b.get_position() - a.get_position();
+                // Coincident objects have no line between them, so pick one
+                let direction = offset.try_normalize().unwrap_or(Vec2::X);
+                let depth =
+                    (a.get_size() + b.get_size()) / 2. - offset.length() + Self::SEPARATION_MARGIN;
+                let share = match (a.is_movable(), b.is_movable()) {
+                    (true, true) => 0.5,
+                    (false, true) => 0.,
+                    (true, false) => 1.,
+                    (false, false) => continue,
+                };
+                self.objects[i].nudge(-direction * depth * share);
+                self.objects[j].nudge(direction * depth * (1. - share));
+            }
+        }
+    }
+
+    /// Replaces all ships among `objects` by `n` ships evenly spaced on a ring around the primary at the center of mass of all bodies, all in the same circular orbit under the `forces`.
+    /// Without a primary there, such as in a binary system, the ring circles the center of mass itself.
+    /// Every ship gets the keymap and tint of its player number, so there can be at most one ship per default keymap.
+    fn spawn_players(
+        &self,
+        objects: &mut Vec<SpaceObject>,
+        n: usize,
+        forces: &Forces,
+    ) -> Result<(), String> {
+        if n == 0 || n > DEFAULT_KEYMAPS.len() {
+            return Err(format!(
+                "cannot place {n} players, only 1 to {} are supported",
+                DEFAULT_KEYMAPS.len()
+            ));
+        }
+        objects.retain(|object| !object.is_ship());
+
+        let (weighted_position, weighted_velocity, total_mass) = objects
+            .iter()
+            .filter(|object| object.is_body())
+            .fold((Vec2::ZERO, Vec2::ZERO, 0.), |(p, v, m), body| {
+                (
+                    p + body.get_position() * body.get_mass(),
+                    v + body.get_velocity() * body.get_mass(),
+                    m + body.get_mass(),
+                )
+            });
+        let (barycenter, barycenter_velocity) = if total_mass > 0. {
+            (
+                weighted_position / total_mass,
+                weighted_velocity / total_mass,
+            )
+        } else {
+            (Vec2::ZERO, Vec2::ZERO)
+        };
+        // A ship at the center of mass finds the primary the same way as every ship in flight
+        let probe = ObjectSpec::player_ship(0, barycenter, Vec2::ZERO).build(&self.sprites)?;
+        let (center, center_velocity, mass) =
+            match Self::find_primary(forces, self.boundary, objects, &probe) {
+                Some(primary) => {
+                    let primary = &objects[primary];
+                    (
+                        primary.get_position(),
+                        primary.get_velocity(),
+                        primary.get_mass(),
+                    )
+                }
+                None => (barycenter, barycenter_velocity, total_mass),
+            };
+
+        for player in 0..n {
+            let offset = Vec2::from_angle(std::f32::consts::TAU * player as f32 / n as f32)
+                * Self::PLAYER_RING_RADIUS;
+            let mut ship = ObjectSpec::player_ship(player, center + offset, center_velocity)
+                .build(&self.sprites)?;
+            let mu = self.circular_mu(
+                forces.gravity().constant,
+                ship.get_mass(),
+                mass,
+                offset.length(),
+            );
+            ship.accelerate(circular_velocity(offset, mu));
+            objects.push(ship);
+        }
+        Ok(())
+    }
+
+    /// Reloads the current scenario from scratch, reporting failure on screen.
+    fn restart(&mut self) {
+        if let Err(err) = self.load_scenario(self.scenario_index) {
+            self.report(format!(
+                "Could not load scenario {}: {err}",
+                self.scenarios[self.scenario_index].name
+            ));
+        }
+    }
+
+    /// Enumerates the scenarios available in the assets directory again, next to the built-in default scenario.
+    fn refresh_scenarios(&mut self) {
+        let current = self.scenarios[self.scenario_index].clone();
+        self.scenarios = vec![Scenario::default()];
+        self.scenarios.extend(Scenario::load_all(&self.assets_dir));
+        // Keep the current scenario selected if it is still available
+        self.scenario_index = self
+            .scenarios
+            .iter()
+            .position(|scenario| scenario.name == current.name)
+            .unwrap_or_else(|| {
+                self.scenarios.push(current);
+                self.scenarios.len() - 1
+            });
+        self.selected_scenario = self.scenario_index;
+    }
+
+    /// Reloads the current scenario and resets the simulation if its file changed since it was loaded.
+    /// If the changed file cannot be loaded, the error is reported and the current simulation kept until the file is fixed.
+    fn watch_scenario(&mut self) {
+        let Some(path) = self.scenarios[self.scenario_index].path.clone() else {
+            return;
+        };
+        let modified = modification_time(&path);
+        if modified.is_none() || modified == self.scenario_modified {
+            return;
+        }
+        // Only report a broken file once per change
+        self.scenario_modified = modified;
+
+        match Scenario::load(&path) {
+            Ok(scenario) => {
+                let name = scenario.name.clone();
+                let previous =
+                    std::mem::replace(&mut self.scenarios[self.scenario_index], scenario);
+                match self.load_scenario(self.scenario_index) {
+                    Ok(()) => self.report(format!("Reloaded scenario {name}")),
+                    Err(err) => {
+                        // Keep restarting into the version that is still running
+                        self.scenarios[self.scenario_index] = previous;
+                        self.report(format!("Could not reload scenario {name}: {err}"))
+                    }
+                }
+            }
+            Err(err) => self.report(format!(
+                "Could not reload scenario {}: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Starts writing the state of all objects passing the record filter to the record path after every physics step.
+    fn start_recording(&mut self) {
+        match Recorder::create(&self.record_path, self.record_filter) {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                self.report(format!("Recording to {}", self.record_path.display()));
+            }
+            Err(err) => self.report(format!(
+                "Could not record to {}: {err}",
+                self.record_path.display()
+            )),
+        }
+    }
+
+    /// Ends the current recording, if any, writing out all buffered rows.
+    fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            match recorder.finish() {
+                Ok(()) => self.report(format!("Recorded to {}", self.record_path.display())),
+                Err(err) => self.report(format!(
+                    "Could not record to {}: {err}",
+                    self.record_path.display()
+                )),
+            }
+        }
+    }
+
+    /// Displays a status message on screen for a short time and prints it to the error output.
+    fn report(&mut self, message: String) {
+        eprintln!("{message}");
+        self.status_message = Some((message, Self::STATUS_MESSAGE_DURATION));
+    }
+
+    /// The current value of an adjustable parameter.
+    fn parameter(&self, parameter: Parameter) -> f32 {
+        match parameter {
+            Parameter::Gravity => self.forces.gravity().constant,
+            Parameter::GravityExponent => self.forces.gravity().exponent,
+            Parameter::Coulomb => self.forces.electric().constant,
+            Parameter::Speed => self.speed,
+            Parameter::Substeps => self.substeps as f32,
+            Parameter::CollisionCooldown => self.collision_cooldown,
+            Parameter::RamDamage => self.collision_rules.ship_ram_damage as f32,
+            Parameter::Restitution => self.collision_rules.restitution,
+            Parameter::MaxObjects => self.max_objects as f32,
+            Parameter::FieldResolution => self.field_resolution as f32,
+            Parameter::NoCollisions => self.collision_rules.disabled as u8 as f32,
+            Parameter::NoGravity => !self.forces.gravity().enabled as u8 as f32,
+            Parameter::FreezeBodies => self.freeze_bodies as u8 as f32,
+            Parameter::InvincibleShips => self.collision_rules.invincible_ships as u8 as f32,
+        }
+    }
+
+    /// Sets an adjustable parameter, rounding the value for integer parameters.
+    fn set_parameter(&mut self, parameter: Parameter, value: f32) {
+        self.predictions = None;
+        match parameter {
+            Parameter::Gravity => self.forces.gravity_mut().constant = value,
+            Parameter::GravityExponent => self.forces.gravity_mut().exponent = value,
+            Parameter::Coulomb => self.forces.electric_mut().constant = value,
+            Parameter::Speed => self.speed = value,
+            Parameter::Substeps => self.substeps = value.round() as usize,
+            Parameter::CollisionCooldown => self.collision_cooldown = value,
+            Parameter::RamDamage => self.collision_rules.ship_ram_damage = value.round() as u8,
+            Parameter::Restitution => self.collision_rules.restitution = value,
+            Parameter::MaxObjects => self.max_objects = value.round() as usize,
+            Parameter::FieldResolution => self.field_resolution = value.round() as usize,
+            Parameter::NoCollisions => self.collision_rules.disabled = value != 0.,
+            Parameter::NoGravity => self.forces.gravity_mut().enabled = value == 0.,
+            Parameter::FreezeBodies => self.freeze_bodies = value != 0.,
+            Parameter::InvincibleShips => self.collision_rules.invincible_ships = value != 0.,
+        }
+    }
+
+    /// Reads the mouse to place new objects: click to set the position, drag to set the velocity, scroll to set mass (or size with shift).
+    /// Holding control when releasing places a ship instead of a body, F5 exports the current scene.
+    fn interact_sandbox(&mut self) {
+        let mouse = self.camera.screen_to_world(mouse_position().into());
+
+        let scroll = mouse_wheel().1;
+        if scroll != 0. {
+            let factor = Self::SANDBOX_SCROLL_STEP.powf(scroll.signum());
+            if is_key_down(KeyCode::LeftShift) {
+                self.sandbox_size *= factor;
+            } else {
+                self.sandbox_mass *= factor;
+            }
+        }
+
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.pending_placement = Some(mouse);
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            if let Some(position) = self.pending_placement.take() {
+                let ships = self.objects.iter().filter(|obj| obj.is_ship()).count();
+                let place_ship = is_key_down(KeyCode::LeftControl);
+                let spec = ObjectSpec {
+                    kind: if place_ship {
+                        ObjectKind::Ship {
+                            keymap: DEFAULT_KEYMAPS[ships % DEFAULT_KEYMAPS.len()],
+                            extended_keymap: None,
+                            assist_key: Some(
+                                DEFAULT_ASSIST_KEYS[ships % DEFAULT_ASSIST_KEYS.len()],
+                            ),
+                            fire_pattern: FirePattern::Single,
+                            pierce: 1,
+                            recoil: 1.,
+                            muzzle_speed: SpaceObject::DEFAULT_MUZZLE_SPEED,
+                            inheritance: 1.,
+                        }
+                    } else {
+                        ObjectKind::Body {
+                            mass: self.sandbox_mass,
+                            size: self.sandbox_size,
+                            density: None,
+                        }
+                    },
+                    position,
+                    velocity: (mouse - position) * Self::SANDBOX_VELOCITY_SCALE,
+                    sprite: if place_ship {
+                        SpriteKey::Ship
+                    } else {
+                        SpriteKey::Earth
+                    },
+                    durability: if place_ship {
+                        Durability::Collisions(3)
+                    } else {
+                        Durability::Indestructible
+                    },
+                    tint: if place_ship {
+                        DEFAULT_TINTS[ships % DEFAULT_TINTS.len()]
+                    } else {
+                        WHITE
+                    },
+                    charge: 0.,
+                    anchored: false,
+                    label: None,
+                    path: None,
+                    glow: None,
+                    collision_layer: None,
+                    collision_mask: None,
+                    gravity_group: 0,
+                };
+                match spec.build(&self.sprites) {
+                    Ok(mut object) => {
+                        object.protect(self.spawn_protection);
+                        self.objects.push(object)
+                    }
+                    Err(err) => self.report(format!("Could not place object: {err}")),
+                }
+            }
+        }
+
+        if is_key_released(KeyCode::F5) {
+            self.export_scene();
+        }
+    }
+
+    /// Writes the current ships and bodies to a timestamped scenario file in the assets directory.
+    fn export_scene(&mut self) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let name = format!("sandbox_{timestamp}");
+        let path =
+            std::path::Path::new(&self.assets_dir).join(format!("{name}.{}", Scenario::EXTENSION));
+
+        let mut scenario = Scenario::from_objects(
+            name,
+            self.forces.gravity().constant,
+            self.forces.electric().constant,
+            &self.objects,
+        );
+        scenario.background = self.scenarios[self.scenario_index].background.clone();
+        scenario.gravity_matrix = self.forces.gravity().groups;
+        match scenario.save(&path) {
+            Ok(()) => self.report(format!("Exported scene to {}", path.display())),
+            Err(err) => self.report(format!("Could not export scene: {err}")),
+        }
+    }
+
+    /// Reads the pause menu controls and acts on the chosen menu action.
+    fn interact_menu(&mut self) {
+        match self.menu.interact() {
+            Some(MenuAction::Resume) => self.paused = false,
+            Some(MenuAction::Restart) => {
+                self.restart();
+                self.paused = false;
+            }
+            Some(MenuAction::PreviousScenario) => {
+                self.selected_scenario =
+                    (self.selected_scenario + self.scenarios.len() - 1) % self.scenarios.len();
+            }
+            Some(MenuAction::NextScenario) => {
+                self.selected_scenario = (self.selected_scenario + 1) % self.scenarios.len();
+            }
+            Some(MenuAction::LoadScenario) => match self.load_scenario(self.selected_scenario) {
+                Ok(()) => self.paused = false,
+                Err(err) => self.report(format!(
+                    "Could not load scenario {}: {err}",
+                    self.scenarios[self.selected_scenario].name
+                )),
+            },
+            Some(MenuAction::Quit) => self.quit_requested = true,
+            None => {}
+        }
     }
 
     /// Reads user input and lets it act on the simulation.
     fn interact(&mut self) {
+        self.watch_scenario();
+        // Pause menu
+        if is_key_released(KeyCode::P) {
+            self.paused = !self.paused;
+            if self.paused {
+                self.refresh_scenarios();
+            }
+        } else if self.paused {
+            self.interact_menu();
+        }
         // Screen interaction
         if is_key_released(KeyCode::F11) {
             set_fullscreen(true);
@@ -95,7 +904,165 @@ impl OrbitsInstance {
         if is_key_released(KeyCode::Escape) {
             set_fullscreen(false);
         }
+        if is_key_released(KeyCode::F2) {
+            self.screenshot_requested = true;
+        }
+        if is_key_released(KeyCode::F6) {
+            self.show_lensing = !self.show_lensing;
+        }
+        if is_key_released(KeyCode::Y) {
+            self.orbit_assist = !self.orbit_assist;
+            self.report(format!(
+                "Orbit assist {}",
+                if self.orbit_assist {
+                    "on, hold a ship's assist key to circularize"
+                } else {
+                    "off"
+                }
+            ));
+        }
+        if is_key_released(KeyCode::F3) {
+            if self.recorder.is_some() {
+                self.stop_recording();
+            } else {
+                self.start_recording();
+            }
+        }
+        if is_key_released(KeyCode::F1) {
+            self.show_tuning = !self.show_tuning;
+        }
+        if self.show_tuning && !self.paused {
+            if let Some((parameter, direction)) = self.tuning.interact() {
+                let value = parameter.adjust(self.parameter(parameter), direction);
+                self.set_parameter(parameter, value);
+            }
+        }
+        if is_key_released(KeyCode::F4) {
+            self.sandbox = !self.sandbox;
+            self.pending_placement = None;
+        }
+        if self.sandbox {
+            self.interact_sandbox();
+        }
+        if is_key_released(KeyCode::Tab) {
+            self.split_screen = !self.split_screen;
+        }
+        if is_key_released(KeyCode::M) {
+            self.camera_mode = match self.camera_mode {
+                CameraMode::Ships => CameraMode::FitAll,
+                CameraMode::FitAll => CameraMode::Ships,
+            };
+        }
+        if is_key_released(KeyCode::Home) {
+            self.camera_fit_timer = Self::CAMERA_FIT_DURATION;
+        }
+        if is_key_released(KeyCode::V) {
+            self.show_labels = !self.show_labels;
+        }
+        if is_key_released(KeyCode::U) {
+            self.show_prediction = !self.show_prediction;
+        }
+        // Hold to rewind the simulation, halting it at the reached state until live physics continue from it
+        if is_key_down(KeyCode::Z) {
+            if let Some(rewound) = self.history.rewind() {
+                self.objects = rewound.objects;
+                self.sim_time = rewound.sim_time;
+                self.contacts = rewound.contacts;
+                self.predictions = None;
+            }
+        }
+        if is_key_released(KeyCode::C) {
+            self.history.resume();
+        }
+        // Right click tracks the object under the cursor, or stops tracking on empty space
+        if is_mouse_button_pressed(MouseButton::Right) {
+            let mouse = self.camera.screen_to_world(mouse_position().into());
+            self.tracked = self
+                .nearest_object(mouse)
+                .map(|index| &self.objects[index])
+                .filter(|object| object.get_position().distance(mouse) <= object.get_size())
+                .map(|object| object.get_id());
+        }
+        // Simulation settings
+        if is_key_released(KeyCode::B) {
+            self.bullet_time = !self.bullet_time;
+        }
+        if is_key_released(KeyCode::Period) {
+            self.substeps = (self.substeps + 1).min(Self::MAX_QUALITY_SUBSTEPS);
+        }
+        if is_key_released(KeyCode::Comma) {
+            self.substeps = (self.substeps - 1).max(1);
+        }
+        if is_key_released(KeyCode::T) {
+            self.adaptive_timestep = !self.adaptive_timestep;
+            self.report(format!(
+                "Adaptive timestep {}",
+                if self.adaptive_timestep { "on" } else { "off" }
+            ));
+        }
+        // Shift switches the gravity keys from the constant to the falloff exponent
+        let gravity = self.forces.gravity_mut();
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            if is_key_released(KeyCode::Equal) {
+                gravity.exponent = Parameter::GravityExponent.adjust(gravity.exponent, 1.);
+            }
+            if is_key_released(KeyCode::Minus) {
+                gravity.exponent = Parameter::GravityExponent.adjust(gravity.exponent, -1.);
+            }
+            if is_key_released(KeyCode::Key0) {
+                gravity.exponent = Self::DEFAULT_GRAVITY_EXPONENT;
+            }
+        } else {
+            if is_key_released(KeyCode::Equal) {
+                gravity.constant *= Self::GRAVITY_STEP;
+            }
+            if is_key_released(KeyCode::Minus) {
+                gravity.constant /= Self::GRAVITY_STEP;
+            }
+            if is_key_released(KeyCode::Key0) {
+                gravity.constant = Self::DEFAULT_GRAVITY;
+            }
+        }
+        gravity.constant = gravity.constant.max(0.);
+        // Predictions follow the gravity keys even while paused
+        if is_key_released(KeyCode::Equal)
+            || is_key_released(KeyCode::Minus)
+            || is_key_released(KeyCode::Key0)
+        {
+            self.predictions = None;
+        }
+        if is_key_released(KeyCode::H) {
+            self.show_field = !self.show_field;
+        }
+        if is_key_released(KeyCode::G) {
+            self.show_wells = !self.show_wells;
+        }
+        if is_key_released(KeyCode::LeftBracket) {
+            self.field_resolution = (self.field_resolution / 2).max(Self::MIN_FIELD_RESOLUTION);
+        }
+        if is_key_released(KeyCode::RightBracket) {
+            self.field_resolution = (self.field_resolution * 2).min(Self::MAX_FIELD_RESOLUTION);
+        }
+        if is_key_released(KeyCode::R) {
+            self.collision_rules.bounce_off_bodies = !self.collision_rules.bounce_off_bodies;
+        }
+        if is_key_released(KeyCode::O) {
+            self.boundary = match self.boundary {
+                Boundary::Remove => Boundary::Wrap,
+                Boundary::Wrap => Boundary::Remove,
+            };
+            self.predictions = None;
+        }
+        if is_key_released(KeyCode::F) {
+            self.collision_rules.friendly_fire = !self.collision_rules.friendly_fire;
+        }
+        if is_key_released(KeyCode::N) {
+            self.collision_rules.ship_bounce = !self.collision_rules.ship_bounce;
+        }
+    }
 
+    /// Lets all ships read their controls and act on them over the time step `dt`.
+    fn control(&mut self, dt: f32) {
         let mut shots = Vec::new();
 
         // Go over all ships and check for their contollers
@@ -104,93 +1071,1073 @@ impl OrbitsInstance {
             .iter_mut()
             .filter(|possible_ship| possible_ship.is_ship())
         {
-            shots.extend(ship.interact(&self.image_cache));
+            shots.extend(ship.interact(&self.sprites, dt));
         }
 
         self.objects.extend(shots);
+
+        if self.orbit_assist {
+            self.circularize(dt);
+        }
+
+        // Make room by evicting the oldest projectiles, ships and bodies are never evicted
+        while self.objects.len() > self.max_objects {
+            let Some(oldest) = self
+                .objects
+                .iter()
+                .enumerate()
+                .filter(|(_, object)| object.is_projectile())
+                .min_by_key(|(_, object)| object.get_id())
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            self.objects.remove(oldest);
+        }
+    }
+
+    /// Steers every ship whose assist key is held gently towards the circular orbit around its dominant primary at its current distance.
+    /// The change of velocity is bounded per time, so the ship settles into the orbit over a few seconds. Fuel is not used.
+    fn circularize(&mut self, dt: f32) {
+        let changes = (0..self.objects.len())
+            .filter(|&index| {
+                self.objects[index]
+                    .get_assist_key()
+                    .is_some_and(is_key_down)
+            })
+            .filter_map(|index| {
+                let primary = self.dominant_primary(index)?;
+                let (ship, primary) = (&self.objects[index], &self.objects[primary]);
+                let offset = ship.get_position() - primary.get_position();
+                let mu = self.circular_mu(
+                    self.forces.gravity().constant,
+                    ship.get_mass(),
+                    primary.get_mass(),
+                    offset.length(),
+                );
+                let target = primary.get_velocity() + circular_velocity(offset, mu);
+                let change = (target - ship.get_velocity())
+                    .clamp_length_max(Self::ORBIT_ASSIST_ACCELERATION * dt);
+                Some((index, change))
+            })
+            .collect::<Vec<_>>();
+
+        for (index, change) in changes {
+            self.objects[index].accelerate(change);
+        }
+    }
+
+    /// The gravitational parameter giving the circular orbit of an object of `mass` around a primary of `primary_mass` at `distance` under the gravitic constant `gravity`.
+    /// Scaling by the distance makes the circular velocity match any falloff exponent.
+    fn circular_mu(&self, gravity: f32, mass: f32, primary_mass: f32, distance: f32) -> f32 {
+        gravity * (mass + primary_mass) / distance.powf(self.forces.gravity().exponent - 2.)
     }
 
-    /// Performs physics updates such as gravity & collision on the simulation.
-    fn update(&mut self) {
-        // For every object, calculate the gravitational influence of all other objects on it.
-        let forces = self
+    /// The simulated time elapsed since the scenario started.
+    fn sim_time(&self) -> f64 {
+        self.sim_time
+    }
+
+    /// The length of the next physics step in ticks.
+    /// With an adaptive timestep, steps shrink while fast objects are close to each other and grow while the system is calm.
+    fn step_size(&self) -> f32 {
+        if !self.adaptive_timestep {
+            return 1.0;
+        }
+
+        let max_speed = self
             .objects
             .iter()
-            .map(|object| {
-                // For every object...
-                let mut f = Vec2::ZERO;
-
-                // Go over every other object
-                for attractor in self.objects.iter() {
-                    // Get the distance vector between the two
-                    let dist = attractor.get_position() - object.get_position();
-                    // If they have are not in the same space, generate a force.
-                    // Prevents division by zero and an object attracting itself.
-                    if dist.length() != 0.0 {
-                        // The gravitational force between the two is in the direction of the distance vector, proportional to their masses and inversely proportional to the square of the distance vectors length.
-                        f += dist.normalize()
-                            * Self::GRAVITY
-                            * object.get_mass()
-                            * attractor.get_mass()
-                            / dist.length_squared();
-                    }
+            .map(|object| object.get_velocity().length())
+            .fold(0., f32::max);
+        let mut min_separation = f32::INFINITY;
+        for (i, a) in self.objects.iter().enumerate() {
+            for b in self.objects.iter().skip(i + 1) {
+                min_separation = min_separation.min(a.get_position().distance(b.get_position()));
+            }
+        }
+
+        (Self::ADAPTIVE_PRECISION * min_separation
+            / (max_speed * self.time_scale).max(f32::MIN_POSITIVE))
+        .clamp(Self::MIN_STEP, Self::MAX_STEP)
+    }
+
+    /// Performs physics updates such as gravity & collision on the simulation, advancing it by `step` ticks.
+    fn update(&mut self, step: f32) {
+        self.update_time_scale(step);
+        let dt = self.time_scale * step / self.substeps as f32;
+
+        for object in self.objects.iter_mut() {
+            object.store_previous();
+            object.update_timers(self.time_scale * step);
+        }
+        self.effects
+            .retain_mut(|effect| effect.update(self.time_scale * step));
+        // Every substep recomputes controls, forces and collisions over its share of the step
+        for _ in 0..self.substeps {
+            self.control(dt);
+            self.substep(dt);
+        }
+
+        self.history
+            .push(&self.objects, self.sim_time, &self.contacts);
+        self.predictions = None;
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.record(self.sim_time, &self.objects) {
+                self.recorder = None;
+                self.report(format!(
+                    "Stopped recording to {}: {err}",
+                    self.record_path.display()
+                ));
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt`, divided into as many equal steps as the fastest object needs not to tunnel through the smallest one.
+    /// All objects move together in every step, so forces and collisions always see all of them at the same time.
+    fn substep(&mut self, dt: f32) {
+        // Objects moving far relative to the smallest object could tunnel through others in a single step.
+        let max_displacement = self
+            .objects
+            .iter()
+            .map(|object| object.get_size())
+            .fold(f32::MAX, f32::min)
+            * Self::SUBSTEP_FRACTION;
+        let fastest = self
+            .objects
+            .iter()
+            .filter(|object| !(self.freeze_bodies && object.is_body()))
+            .map(|object| object.get_velocity().length())
+            .fold(0., f32::max);
+        let steps =
+            ((fastest * dt / max_displacement).ceil() as usize).clamp(1, Self::MAX_SUBSTEPS);
+
+        for _ in 0..steps {
+            self.integrate(dt / steps as f32);
+        }
+    }
+
+    /// Advances forces, movement, collisions and boundaries of the simulation by `dt`.
+    fn integrate(&mut self, dt: f32) {
+        self.sim_time += dt as f64;
+
+        // For every object, calculate the gravitational and electric influence of all other objects on it.
+        let forces = (0..self.objects.len())
+            .map(|index| self.force_on(index))
+            .collect::<Vec<_>>();
+
+        // Then apply accelerations and velocities to all objects at once, frozen bodies do not move at all.
+        for (object, force) in self.objects.iter_mut().zip(forces) {
+            if !(self.freeze_bodies && object.is_body()) {
+                object.perform_movement(Some(force), dt, self.max_speed);
+            }
+        }
+
+        // Remove objects whose state became numerically unstable before they can spread it through gravity
+        let mut index = 0;
+        while index < self.objects.len() {
+            if let Err(reason) = self.objects[index].check_finite() {
+                let object = self.objects.remove(index);
+                self.report(format!(
+                    "Removed unstable {} {index}: {reason}",
+                    object.kind_name()
+                ));
+            } else {
+                index += 1;
+            }
+        }
+
+        // Now check for collisions, resolving them ordered by the objects' ids so the outcome does not depend on their order in the vector
+        let mut pairs = self
+            .overlapping_pairs()
+            .into_iter()
+            .map(|(i, j)| {
+                let (a, b) = (self.objects[i].get_id(), self.objects[j].get_id());
+                if a < b {
+                    (a, b, i, j)
+                } else {
+                    (b, a, j, i)
                 }
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
 
-                f
+        // Pairs that collided recently cannot collide again until their cooldown expires, so sustained contact does not drain durability every tick
+        let (now, cooldown) = (self.sim_time, self.collision_cooldown as f64);
+        self.contacts.retain(|_, &mut time| now - time < cooldown);
+        for (first_id, second_id, first, second) in pairs {
+            if self.contacts.contains_key(&(first_id, second_id)) {
+                continue;
+            }
+            self.contacts.insert((first_id, second_id), now);
+            let (first, second) = if first < second {
+                let (left, right) = self.objects.split_at_mut(second);
+                (&mut left[first], &mut right[0])
+            } else {
+                let (left, right) = self.objects.split_at_mut(first);
+                (&mut right[0], &mut left[second])
+            };
+            // Hits on ships and crashes between bodies shake the camera, measured before the collision changes velocities
+            let energy = first.impact_energy(second);
+            let max_shake = if first.is_body() && second.is_body() {
+                Some(Self::MAX_SHAKE)
+            } else if first.is_ship() || second.is_ship() {
+                Some(Self::MAX_SHIP_SHAKE)
+            } else {
+                None
+            };
+            if let Some(contact) = first.collide(second, &self.collision_rules) {
+                self.effects.push(ImpactFlash::new(contact));
+                if let Some(max_shake) = max_shake {
+                    self.shake(energy, max_shake);
+                }
+            }
+        }
+
+        // Track the ships' gravity assists around their primaries
+        let targets = (0..self.objects.len())
+            .map(|index| {
+                self.dominant_primary(index).map(|primary| {
+                    let body = &self.objects[primary];
+                    (body.get_position(), body.get_size())
+                })
             })
             .collect::<Vec<_>>();
+        for (ship, target) in self.objects.iter_mut().zip(targets) {
+            if ship.is_ship() {
+                ship.record_flight(target, dt);
+            }
+        }
 
-        // Then apply accelerations and velocities.
-        for (object, &force) in self.objects.iter_mut().zip(forces.iter()) {
-            object.perform_movement(Some(force));
+        match self.boundary {
+            // Delete all objects too far from the origin
+            Boundary::Remove => self.objects.retain(|object| {
+                (object.get_position().length() <= Self::ARENA_SIZE || object.is_ship())
+                    && object.collisions_left()
+            }),
+            // Move objects that left the arena to the opposite side
+            Boundary::Wrap => {
+                for object in self.objects.iter_mut() {
+                    object.wrap(Self::ARENA_SIZE);
+                }
+                self.objects.retain(|object| object.collisions_left())
+            }
         }
+    }
 
-        // Now check for collisions
-        for i in 0..self.objects.len() {
-            for j in (i + 1)..self.objects.len() {
-                let (left, right) = self.objects.split_at_mut(j);
-                left[i].collide(&mut right[0]);
+    /// The indices of all objects whose center lies within `radius` of `center`, including those exactly on the boundary, in ascending order.
+    fn objects_in_radius(&self, center: Vec2, radius: f32) -> Vec<usize> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.get_position().distance(center) <= radius)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The index of the object whose center is closest to `point`, preferring the lowest index on ties.
+    fn nearest_object(&self, point: Vec2) -> Option<usize> {
+        self.objects
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.get_position()
+                    .distance_squared(point)
+                    .total_cmp(&b.get_position().distance_squared(point))
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Finds the object the object at `index` mainly orbits, see `find_primary`.
+    fn dominant_primary(&self, index: usize) -> Option<usize> {
+        Self::find_primary(
+            &self.forces,
+            self.boundary,
+            &self.objects,
+            &self.objects[index],
+        )
+    }
+
+    /// Finds the primary of `object` among `objects`: the heavier object whose gravity, as the `forces` apply it within the `boundary`, pulls on it much stronger than any other's.
+    /// Returns `None` if no heavier object pulls on it or the strongest pulls are too close to call, such as between two equal stars.
+    fn find_primary(
+        forces: &Forces,
+        boundary: Boundary,
+        objects: &[SpaceObject],
+        object: &SpaceObject,
+    ) -> Option<usize> {
+        let mut strongest = None;
+        let mut second = 0.;
+
+        for (index, attractor) in objects.iter().enumerate() {
+            if attractor.get_id() == object.get_id() || attractor.get_mass() <= object.get_mass() {
+                continue;
+            }
+            let dist = Self::separation(boundary, object.get_position(), attractor.get_position());
+            if dist.length() == 0. {
+                continue;
+            }
+            // Attractors cut off or in ignored gravity groups do not pull at all
+            let pull = forces.gravity().force(object, attractor, dist).length();
+            if pull <= 0. {
+                continue;
+            }
+            match strongest {
+                Some((_, max)) if pull <= max => second = f32::max(second, pull),
+                Some((_, max)) => {
+                    second = max;
+                    strongest = Some((index, pull));
+                }
+                None => strongest = Some((index, pull)),
             }
         }
 
-        // Delete all objects too far from the origin
-        self.objects.retain(|object| {
-            (object.get_position().length() <= 1000. || object.is_ship())
-                && object.collisions_left()
+        strongest
+            .filter(|&(_, max)| max >= second * Self::DOMINANCE_RATIO)
+            .map(|(primary, _)| primary)
+    }
+
+    /// Classifies the orbit of the object at `index` around its dominant primary.
+    /// Returns `None` without a clear primary, or if gravity is switched off or does not follow the inverse-square law, which the orbital energy requires.
+    fn orbit_class(&self, index: usize) -> Option<OrbitClass> {
+        if !self.forces.gravity().enabled || self.forces.gravity().exponent != 2. {
+            return None;
+        }
+        let primary = self.dominant_primary(index)?;
+        let (object, attractor) = (&self.objects[index], &self.objects[primary]);
+        OrbitClass::from_state(
+            object.get_position() - attractor.get_position(),
+            object.get_velocity() - attractor.get_velocity(),
+            self.forces.gravity().constant * (object.get_mass() + attractor.get_mass()),
+        )
+    }
+
+    /// The index of the tracked object, its dominant primary and its current osculating orbit around it, if there is a clear primary and gravity holds the object in orbit at all.
+    fn tracked_orbit(&self) -> Option<(usize, usize, OrbitalElements)> {
+        let index = self
+            .objects
+            .iter()
+            .position(|object| Some(object.get_id()) == self.tracked)?;
+        // Keplerian orbits only exist under inverse-square gravity
+        if !self.forces.gravity().enabled || self.forces.gravity().exponent != 2. {
+            return None;
+        }
+        let primary = self.dominant_primary(index)?;
+        let (object, attractor) = (&self.objects[index], &self.objects[primary]);
+
+        let elements = OrbitalElements::from_state(
+            object.get_position() - attractor.get_position(),
+            object.get_velocity() - attractor.get_velocity(),
+            self.forces.gravity().constant * (object.get_mass() + attractor.get_mass()),
+        )?;
+        Some((index, primary, elements))
+    }
+
+    /// Calculates the gravitational and electric influence of all other objects on the object at `index`.
+    fn force_on(&self, index: usize) -> Vec2 {
+        let object = &self.objects[index];
+        self.objects.iter().fold(Vec2::ZERO, |f, attractor| {
+            f + self.force_between(object, attractor)
         })
     }
 
-    /// Draws the current state to the screen.
-    fn draw(&mut self) {
-        // Clear the current frame
-        clear_background(BLACK);
+    /// The distance vector pointing from `from` to `to`, or to its nearest periodic image if the `boundary` wraps around the arena.
+    fn separation(boundary: Boundary, from: Vec2, to: Vec2) -> Vec2 {
+        let dist = to - from;
+        if boundary == Boundary::Wrap {
+            dist - (dist / (2. * Self::ARENA_SIZE)).round() * 2. * Self::ARENA_SIZE
+        } else {
+            dist
+        }
+    }
 
-        // Draw UI
+    /// Calculates the sum of the forces of all force models the attractor exerts on the object.
+    fn force_between(&self, object: &SpaceObject, attractor: &SpaceObject) -> Vec2 {
+        // On a torus, the attractor pulls from its nearest periodic image
+        let dist = Self::separation(
+            self.boundary,
+            object.get_position(),
+            attractor.get_position(),
+        );
+        // Prevents division by zero and an object attracting itself.
+        if dist.length() == 0.0 {
+            return Vec2::ZERO;
+        }
+        self.forces.force(object, attractor, dist)
+    }
 
-        set_default_camera();
-        draw_text("Ship 1", 0., 20., 12., WHITE);
+    /// Forward-simulates the path of the ship at `index` as if the player kept holding its current thrust input, for as long as its fuel lasts.
+    /// Projectiles are ignored, and the prediction stops early once the ship hits another object or, unless the arena wraps around, leaves it.
+    /// The path is split into separate pieces wherever the ship wraps around the arena.
+    fn predict_trajectory(&self, index: usize) -> Vec<Vec<Vec2>> {
+        let id = self.objects[index].get_id();
+        let thrusters = self.objects[index].thrusters();
+        let mut objects = self
+            .objects
+            .iter()
+            .filter(|object| !object.is_projectile())
+            .cloned()
+            .collect::<Vec<_>>();
+        let Some(ship) = objects.iter().position(|object| object.get_id() == id) else {
+            return Vec::new();
+        };
 
-        // Draw simulation
+        let mut paths = Vec::new();
+        let mut path = vec![objects[ship].get_position()];
+        for _ in 0..Self::PREDICTION_STEPS {
+            // Thrust is applied before moving, as ships read their controls before every physics step
+            objects[ship].burn(&thrusters, Self::PREDICTION_STEP);
+            let forces = objects
+                .iter()
+                .map(|object| {
+                    objects.iter().fold(Vec2::ZERO, |f, attractor| {
+                        f + self.force_between(object, attractor)
+                    })
+                })
+                .collect::<Vec<_>>();
+            for (object, force) in objects.iter_mut().zip(forces) {
+                object.perform_movement(Some(force), Self::PREDICTION_STEP, self.max_speed);
+            }
 
-        let (w, h) = (screen_width(), screen_height());
+            let mut position = objects[ship].get_position();
+            if self.boundary == Boundary::Wrap {
+                for object in objects.iter_mut() {
+                    object.wrap(Self::ARENA_SIZE);
+                }
+                // Finish the piece at the edge and continue on the opposite side
+                let wrapped = objects[ship].get_position();
+                if wrapped != position {
+                    path.push(position);
+                    paths.push(std::mem::take(&mut path));
+                    position = wrapped;
+                }
+            }
+            path.push(position);
+            if (self.boundary != Boundary::Wrap
+                && (position.x.abs() > Self::ARENA_SIZE || position.y.abs() > Self::ARENA_SIZE))
+                || objects
+                    .iter()
+                    .enumerate()
+                    .any(|(other, object)| other != ship && object.overlaps(&objects[ship]))
+            {
+                break;
+            }
+        }
+        paths.push(path);
+        paths
+    }
 
-        let mut scale: f32 = 0.5;
+    /// Triggers slow motion windows on imminent collisions and moves the time scale towards its target.
+    fn update_time_scale(&mut self, step: f32) {
+        self.slow_motion_timer = (self.slow_motion_timer - step).max(0.);
+        self.slow_motion_cooldown = (self.slow_motion_cooldown - step).max(0.);
 
-        for object in self.objects.iter().filter(|obj| obj.is_ship()) {
-            // 2.2 to leave some padding
-            let w_scale = object.get_position().x.abs() / w * 2.2;
-            let h_scale = object.get_position().y.abs() / h * 2.2;
+        if !self.bullet_time {
+            self.slow_motion_timer = 0.;
+        } else if self.slow_motion_timer <= 0.
+            && self.slow_motion_cooldown <= 0.
+            && self.near_collision()
+        {
+            self.slow_motion_timer = Self::SLOW_MOTION_DURATION;
+            // The cooldown ensures persistently close objects cannot keep the simulation in slow motion.
+            self.slow_motion_cooldown = Self::SLOW_MOTION_DURATION + Self::SLOW_MOTION_COOLDOWN;
+        }
 
-            scale = scale.max(w_scale).max(h_scale);
+        let target = if self.slow_motion_timer > 0. {
+            Self::SLOW_MOTION_SCALE * self.speed
+        } else {
+            self.speed
+        };
+        self.time_scale += (target - self.time_scale) * (Self::TIME_SCALE_LERP * step).min(1.);
+    }
+
+    /// Checks wether two ships or a projectile and a ship are about to collide within the next few ticks.
+    fn near_collision(&self) -> bool {
+        for (i, a) in self.objects.iter().enumerate() {
+            for b in self.objects.iter().skip(i + 1) {
+                // Only ship-ship and projectile-ship pairs are interesting
+                if !(a.is_ship() && (b.is_ship() || b.is_projectile())
+                    || b.is_ship() && a.is_projectile())
+                {
+                    continue;
+                }
+                let rel_pos = b.get_position() - a.get_position();
+                let rel_vel = b.get_velocity() - a.get_velocity();
+                // Only pairs that are actively approaching each other count
+                if rel_pos.dot(rel_vel) >= 0. {
+                    continue;
+                }
+                // Time of closest approach along the linearly extrapolated paths
+                let t = (-rel_pos.dot(rel_vel) / rel_vel.length_squared())
+                    .min(Self::SLOW_MOTION_LOOKAHEAD);
+                if (rel_pos + rel_vel * t).length() * 2. < a.get_size() + b.get_size() {
+                    return true;
+                }
+            }
         }
+        false
+    }
+
+    /// Draws the simulation with a single camera framing all ships, or all objects depending on the camera mode, offset by the `shake` in pixels.
+    fn draw_single_camera(&mut self, ships: &[usize], alpha: f32, shake: Vec2) {
+        let (w, h) = (screen_width(), screen_height());
+
+        self.camera_fit_timer = (self.camera_fit_timer - get_frame_time()).max(0.);
+        let (target, scale) =
+            if self.camera_mode == CameraMode::FitAll || self.camera_fit_timer > 0. {
+                self.fit_all(alpha, w, h)
+            } else {
+                let mut scale = self.min_camera_scale;
+
+                for &index in ships {
+                    let position = self.objects[index].get_interpolated_position(alpha);
+                    // 2.2 to leave some padding
+                    let w_scale = position.x.abs() / w * 2.2;
+                    let h_scale = position.y.abs() / h * 2.2;
+
+                    scale = scale.max(w_scale).max(h_scale);
+                }
+                (Vec2::ZERO, scale)
+            };
+
+        // Ease towards the clamped target scale and position instead of snapping to them
+        let scale = scale.min(self.max_camera_scale);
+        self.camera_scale += (scale - self.camera_scale) * Self::CAMERA_SMOOTHING;
+        self.camera_target += (target - self.camera_target) * Self::CAMERA_SMOOTHING;
 
         // Camera is -1 to 1, so width and height 2. Correct by that and the reciprocal of screen width.
-        self.camera.zoom = Vec2::new(1. / w, 1. / h) / scale * 2.0;
+        self.camera.zoom = Vec2::new(1. / w, 1. / h) / self.camera_scale * 2.0;
+        self.camera.target = self.camera_target + shake * self.camera_scale;
+        self.camera.viewport = None;
+
+        if self.show_lensing {
+            let lenses = self.lenses(alpha, w, h);
+            let lensing = self.lensing.get_or_insert_with(Lensing::new);
+            self.camera.render_target = Some(lensing.target());
+            // Empty space stays transparent, so the background shows through
+            set_camera(&self.camera);
+            clear_background(BLANK);
+            self.draw_world(alpha);
+            self.camera.render_target = None;
+            if let Some(lensing) = &self.lensing {
+                lensing.draw(&lenses);
+            }
+        } else {
+            self.draw_world(alpha);
+        }
+
+        // Draw UI
+        set_default_camera();
+        self.draw_labels(alpha, w, h);
+
+        for (i, &index) in ships.iter().enumerate() {
+            Self::draw_ship_status(&self.objects[index], i, 20. + 40. * i as f32);
+        }
+    }
+
+    /// The screen positions and radii of the heaviest bodies bending the world while lensing is shown.
+    fn lenses(&self, alpha: f32, w: f32, h: f32) -> Vec<Lens> {
+        let mut bodies = self
+            .objects
+            .iter()
+            .filter(|object| object.is_body() && object.get_mass() >= Self::LENS_MIN_MASS)
+            .collect::<Vec<_>>();
+        bodies.sort_by(|a, b| b.get_mass().total_cmp(&a.get_mass()));
+        bodies
+            .iter()
+            .take(Lensing::MAX_LENSES)
+            .map(|body| Lens {
+                center: self.world_to_screen(body.get_interpolated_position(alpha), w, h),
+                radius: body.get_size() / 2. / self.camera_scale,
+            })
+            .collect()
+    }
+
+    /// Returns the center and scale of a camera containing every object, including its size, on a screen of size `w` by `h`.
+    /// Without any objects, or with all of them in one spot, the scale falls back to the minimum camera scale.
+    fn fit_all(&self, alpha: f32, w: f32, h: f32) -> (Vec2, f32) {
+        let Some((min, max)) = self
+            .objects
+            .iter()
+            .map(|object| {
+                let position = object.get_interpolated_position(alpha);
+                let half_size = Vec2::splat(object.get_size() / 2.);
+                (position - half_size, position + half_size)
+            })
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+        else {
+            return (Vec2::ZERO, self.min_camera_scale);
+        };
+
+        // 1.1 instead of 1 to leave some padding
+        let extent = max - min;
+        let scale = (extent.x / w * 1.1)
+            .max(extent.y / h * 1.1)
+            .max(self.min_camera_scale);
+        ((min + max) / 2., scale)
+    }
+
+    /// Draws the simulation split into one pane per ship, each with its own camera following that ship, offset by the `shake` in pixels.
+    fn draw_split_screen(&mut self, ships: &[usize], alpha: f32, shake: Vec2) {
+        let h = screen_height();
+        let pane_width = screen_width() / ships.len() as f32;
+
+        for (i, &index) in ships.iter().enumerate() {
+            let viewport = Some((
+                (pane_width * i as f32) as i32,
+                0,
+                pane_width as i32,
+                h as i32,
+            ));
+
+            self.camera.zoom = Vec2::new(1. / pane_width, 1. / h) / Self::SPLIT_SCREEN_SCALE * 2.0;
+            self.camera.target = self.objects[index].get_interpolated_position(alpha)
+                + shake * Self::SPLIT_SCREEN_SCALE;
+            self.camera.viewport = viewport;
+
+            self.draw_world(alpha);
+
+            // Draw the UI of this pane in pane-local screen coordinates
+            let mut ui_camera = Camera2D::from_display_rect(Rect::new(0., 0., pane_width, h));
+            ui_camera.viewport = viewport;
+            set_camera(&ui_camera);
+            self.draw_labels(alpha, pane_width, h);
+
+            Self::draw_ship_status(&self.objects[index], i, 20.);
+            if i > 0 {
+                draw_line(0., 0., 0., h, 2., DARKGRAY);
+            }
+        }
+    }
 
+    /// Draws the field and all objects through the current camera.
+    fn draw_world(&self, alpha: f32) {
         set_camera(&self.camera);
+        // The camera maps its viewport to -1 to 1, so half its width in pixels per unit of zoom
+        let pixels_per_unit = self.camera.zoom.x
+            * self
+                .camera
+                .viewport
+                .map_or(screen_width(), |(_, _, width, _)| width as f32)
+            / 2.;
+
+        // Without gravity there is no field to show
+        if self.show_field && self.forces.gravity().enabled {
+            self.draw_field();
+        }
+
+        if self.show_wells {
+            for body in self.objects.iter().filter(|object| object.is_body()) {
+                body.draw_well(alpha);
+            }
+        }
+
+        // Predicted paths under the currently held thrust, to line up gravity assists
+        if self.show_prediction {
+            let predictions = self.predictions.as_ref();
+            for index in (0..self.objects.len()).filter(|&index| self.objects[index].is_ship()) {
+                // Colored by wether the ship is currently bound to or escaping from its primary
+                let color = Color {
+                    a: 0.5,
+                    ..self
+                        .orbit_class(index)
+                        .map_or(self.objects[index].get_tint(), OrbitClass::color)
+                };
+                for segment in predictions
+                    .and_then(|predictions| predictions.get(&self.objects[index].get_id()))
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|path| path.windows(2))
+                {
+                    draw_line(
+                        segment[0].x,
+                        segment[0].y,
+                        segment[1].x,
+                        segment[1].y,
+                        1.,
+                        color,
+                    );
+                }
+            }
+        }
+
+        for object in self.objects.iter() {
+            object.draw(alpha, self.sim_time() as f32, pixels_per_unit);
+        }
+        for effect in self.effects.iter() {
+            effect.draw();
+        }
+
+        // Mark the apsides of the tracked object's orbit around its primary
+        if let Some((index, primary, elements)) = self.tracked_orbit() {
+            let center = self.objects[primary].get_interpolated_position(alpha);
+            let position = self.objects[index].get_interpolated_position(alpha);
+            draw_circle_lines(
+                position.x,
+                position.y,
+                self.objects[index].get_size(),
+                1.,
+                YELLOW,
+            );
+
+            let apsides = [(Some(elements.periapsis), "Pe"), (elements.apoapsis, "Ap")];
+            for (apsis, label) in apsides {
+                if let Some(apsis) = apsis.map(|apsis| center + apsis) {
+                    draw_circle(apsis.x, apsis.y, 3., SKYBLUE);
+                    draw_text(label, apsis.x + 5., apsis.y - 5., 16., SKYBLUE);
+                }
+            }
+        }
+
+        // Preview the object being placed in sandbox mode with its velocity
+        if let Some(position) = self.pending_placement {
+            let mouse = self.camera.screen_to_world(mouse_position().into());
+            draw_circle_lines(position.x, position.y, self.sandbox_size / 2., 1., GREEN);
+            draw_line(position.x, position.y, mouse.x, mouse.y, 1., GREEN);
+        }
+    }
+
+    /// Projects a world position through the current camera to pixel coordinates within a pane of the given size.
+    fn world_to_screen(&self, point: Vec2, width: f32, height: f32) -> Vec2 {
+        let projected = self
+            .camera
+            .matrix()
+            .project_point3(vec3(point.x, point.y, 0.));
+        vec2(
+            (projected.x / 2. + 0.5) * width,
+            (0.5 - projected.y / 2.) * height,
+        )
+    }
 
+    /// Draws the name of every labelled object above it in a pane of the given size, kept inside the pane near its borders.
+    /// Ships without a name are labelled by their number.
+    fn draw_labels(&self, alpha: f32, width: f32, height: f32) {
+        if !self.show_labels {
+            return;
+        }
+
+        let mut ships = 0;
         for object in self.objects.iter() {
-            object.draw();
+            let label = match object.get_label() {
+                Some(label) => label.to_owned(),
+                None if object.is_ship() => format!("Ship {}", ships + 1),
+                None => continue,
+            };
+            if object.is_ship() {
+                ships += 1;
+            }
+
+            let position = object.get_interpolated_position(alpha);
+            let center = self.world_to_screen(position, width, height);
+            let radius = self
+                .world_to_screen(position + Vec2::X * object.get_size() / 2., width, height)
+                .distance(center);
+            let size = measure_text(&label, None, 16, 1.);
+            let x = (center.x - size.width / 2.).clamp(0., (width - size.width).max(0.));
+            let y = (center.y - radius - 4.).clamp(size.height, height);
+            draw_text(&label, x, y, 16., object.get_tint());
+        }
+    }
+
+    /// Draws a panel describing the tracked object and its orbit in the top right corner of the screen.
+    fn draw_inspector(&self) {
+        let Some(index) = self
+            .objects
+            .iter()
+            .position(|object| Some(object.get_id()) == self.tracked)
+        else {
+            return;
+        };
+        let object = &self.objects[index];
+
+        let mut lines = vec![
+            format!("Tracking {} {}", object.kind_name(), object.get_id()),
+            format!(
+                "Mass {:.2}  Speed {:.2}",
+                object.get_mass(),
+                object.get_velocity().length()
+            ),
+        ];
+        if let Some((_, primary, elements)) = self.tracked_orbit() {
+            lines.push(format!(
+                "Orbiting {} {}",
+                self.objects[primary].kind_name(),
+                self.objects[primary].get_id()
+            ));
+            lines.push(format!("Eccentricity {:.3}", elements.eccentricity));
+            if let Some(period) = elements.period {
+                lines.push(format!("Period {:.1} s", period * Self::TICK));
+            }
+        }
+        if let Some(class) = self.orbit_class(index) {
+            lines.push(format!("Orbit {}", class.name()));
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                screen_width() - 200.,
+                72. + 16. * i as f32,
+                16.,
+                YELLOW,
+            );
+        }
+    }
+
+    /// Draws the label, health bar, fuel and ammunition gauges and flight record of the `i`-th ship at height `y`.
+    fn draw_ship_status(ship: &SpaceObject, i: usize, y: f32) {
+        draw_text(&format!("Ship {}", i + 1), 0., y, 12., ship.get_tint());
+        // Health bar with the fuel and ammunition gauges next to it
+        if let Some(health) = ship.get_health() {
+            draw_rectangle(0., y + 4., 64., 6., DARKGRAY);
+            draw_rectangle(0., y + 4., 64. * health, 6., RED);
+        }
+        if let Some(fuel) = ship.get_fuel() {
+            draw_rectangle(68., y + 4., 64., 6., DARKGRAY);
+            draw_rectangle(68., y + 4., 64. * fuel, 6., ORANGE);
+        }
+        if let Some(ammo) = ship.get_ammo() {
+            draw_rectangle(136., y + 4., 64., 6., DARKGRAY);
+            draw_rectangle(136., y + 4., 64. * ammo, 6., YELLOW);
+        }
+        if let Some(record) = ship.get_record() {
+            draw_text(
+                &format!(
+                    "Closest {:.1}  Top speed {:.2}  Skim {:.0}",
+                    record.closest_approach, record.top_speed, record.skim_score
+                ),
+                0.,
+                y + 22.,
+                12.,
+                GRAY,
+            );
+        }
+    }
+
+    /// Draws a heatmap of the gravitational field strength of all celestial bodies across the area visible to the current camera.
+    /// The field is the force on a test object of unit mass, computed exactly as the simulation does.
+    fn draw_field(&self) {
+        // The camera shows 1 / zoom world units in every direction around its target
+        let half_extent = (Vec2::ONE / self.camera.zoom).abs();
+        let top_left = self.camera.target - half_extent;
+        let cell = 2. * half_extent / self.field_resolution as f32;
+
+        // Sample the acceleration magnitude at the center of every cell
+        let samples = (0..self.field_resolution * self.field_resolution)
+            .map(|index| {
+                let point = top_left
+                    + cell
+                        * Vec2::new(
+                            (index % self.field_resolution) as f32 + 0.5,
+                            (index / self.field_resolution) as f32 + 0.5,
+                        );
+                let probe = SpaceObject::probe(point, 1.);
+                self.objects
+                    .iter()
+                    .filter(|object| object.is_body())
+                    .fold(Vec2::ZERO, |f, body| f + self.force_between(&probe, body))
+                    .length()
+            })
+            .collect::<Vec<_>>();
+
+        // The field falls off steeply with distance, so interpolate colors on a logarithmic scale
+        let (min, max) = samples.iter().fold((f32::MAX, f32::MIN), |(min, max), &s| {
+            (min.min(s), max.max(s))
+        });
+        let (min, max) = (min.max(f32::EPSILON).ln(), max.max(f32::EPSILON).ln());
+
+        for (index, sample) in samples.iter().enumerate() {
+            let t = if max > min {
+                (sample.max(f32::EPSILON).ln() - min) / (max - min)
+            } else {
+                0.
+            };
+            let corner = top_left
+                + cell
+                    * Vec2::new(
+                        (index % self.field_resolution) as f32,
+                        (index / self.field_resolution) as f32,
+                    );
+            draw_rectangle(
+                corner.x,
+                corner.y,
+                cell.x,
+                cell.y,
+                Color::new(t, 0.2 * t, 1. - t, 0.35),
+            );
+        }
+    }
+
+    /// Starts a camera shake growing with the impact energy up to `max` pixels, unless a stronger one is still running.
+    fn shake(&mut self, energy: f32, max: f32) {
+        if !energy.is_finite() {
+            return;
+        }
+        let intensity = (Self::BASE_SHAKE + energy.max(0.).ln_1p() * Self::SHAKE_PER_ENERGY)
+            .min(max)
+            * self.screen_shake;
+        if intensity > self.shake_magnitude() {
+            self.shake_intensity = intensity;
+            self.shake_timer = Self::SHAKE_DURATION;
+        }
+    }
+
+    /// The distance in pixels the camera is currently shaken by, easing out to zero as the shake runs out.
+    fn shake_magnitude(&self) -> f32 {
+        self.shake_intensity * (self.shake_timer / Self::SHAKE_DURATION).powi(2)
+    }
+
+    /// Lets the camera shake run out over the last frame and returns its random offset in pixels for this frame.
+    /// Only the rendered camera is moved, never the simulation.
+    fn shake_offset(&mut self) -> Vec2 {
+        self.shake_timer = (self.shake_timer - get_frame_time()).max(0.);
+        if self.shake_timer == 0. {
+            return Vec2::ZERO;
+        }
+        let angle = macroquad::rand::gen_range(0., std::f32::consts::TAU);
+        Vec2::from_angle(angle) * self.shake_magnitude()
+    }
+
+    /// Draws the current state to the screen, interpolating objects by `alpha` between the previous and current physics tick.
+    fn draw(&mut self, alpha: f32) {
+        // Predicting is expensive, so it only happens once for all panes after the objects changed
+        if self.show_prediction && self.predictions.is_none() {
+            self.predictions = Some(
+                (0..self.objects.len())
+                    .filter(|&index| self.objects[index].is_ship())
+                    .map(|index| (self.objects[index].get_id(), self.predict_trajectory(index)))
+                    .collect(),
+            );
+        }
+        let shake = self.shake_offset();
+        // Clear the current frame
+        self.background.draw(self.background_texture.as_ref());
+
+        let ships = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, obj)| obj.is_ship())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        if self.split_screen && ships.len() > 1 {
+            self.draw_split_screen(&ships, alpha, shake);
+        } else {
+            self.draw_single_camera(&ships, alpha, shake);
+        }
+
+        // Draw global UI across the whole screen
+        set_camera(&Camera2D::from_display_rect(Rect::new(
+            0.,
+            0.,
+            screen_width(),
+            screen_height(),
+        )));
+
+        draw_text(
+            &format!("Gravity: {:.4}", self.forces.gravity().constant),
+            screen_width() - 120.,
+            20.,
+            16.,
+            WHITE,
+        );
+        draw_text(
+            &format!("Substeps: {}", self.substeps),
+            screen_width() - 120.,
+            36.,
+            16.,
+            WHITE,
+        );
+        draw_text(
+            &format!("Falloff: 1/r^{:.2}", self.forces.gravity().exponent),
+            screen_width() - 120.,
+            52.,
+            16.,
+            WHITE,
+        );
+        self.draw_inspector();
+        if self.sandbox {
+            draw_text(
+                &format!(
+                    "Sandbox: mass {:.1}, size {:.1} (Ctrl: ship, F5: export)",
+                    self.sandbox_mass, self.sandbox_size
+                ),
+                0.,
+                screen_height() - 28.,
+                16.,
+                GREEN,
+            );
+        }
+
+        // Capture the finished frame before any status messages are drawn on top of it
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            match save_screenshot() {
+                Ok(path) => {
+                    self.status_message = Some((
+                        format!("Saved screenshot to {path}"),
+                        Self::STATUS_MESSAGE_DURATION,
+                    ))
+                }
+                Err(err) => self.report(format!("Could not save screenshot: {err}")),
+            }
+        }
+
+        if self.history.is_rewound() {
+            draw_text(
+                "Rewound (hold Z to rewind further, C to continue)",
+                0.,
+                screen_height() - 48.,
+                16.,
+                SKYBLUE,
+            );
+        }
+
+        if self.show_tuning {
+            self.tuning.draw(|parameter| self.parameter(parameter));
+        }
+
+        if self.paused {
+            self.menu.draw(&self.scenarios[self.selected_scenario].name);
         }
+
+        if let Some((message, timer)) = &mut self.status_message {
+            draw_text(message, 0., screen_height() - 8., 16., WHITE);
+            *timer -= 1.;
+            if *timer <= 0. {
+                self.status_message = None;
+            }
+        }
+    }
+}
+
+/// Returns the time the file at `path` was last modified, if it can be determined.
+fn modification_time(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Saves the current contents of the screen to a timestamped PNG file in the working directory and returns its path.
+fn save_screenshot() -> Result<String, Box<dyn std::error::Error>> {
+    let image = get_screen_data();
+    let (width, height) = (image.width as usize, image.height as usize);
+
+    // The screen data is stored bottom-up, so flip it before saving
+    let mut bytes = Vec::with_capacity(image.bytes.len());
+    for row in image.bytes.chunks_exact(width * 4).rev() {
+        bytes.extend_from_slice(row);
     }
+
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let path = format!("screenshot_{}.png", timestamp.as_millis());
+
+    let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&bytes)?;
+
+    Ok(path)
 }
@@ -0,0 +1,94 @@
+use macroquad::prelude::*;
+
+/// The entries of the pause menu, in the order they are displayed.
+const ENTRIES: [MenuEntry; 4] = [
+    MenuEntry::Resume,
+    MenuEntry::Restart,
+    MenuEntry::SelectScenario,
+    MenuEntry::Quit,
+];
+
+/// An entry of the pause menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuEntry {
+    Resume,
+    Restart,
+    SelectScenario,
+    Quit,
+}
+
+/// An action chosen by the user in the pause menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    /// Close the menu and continue the simulation.
+    Resume,
+    /// Reload the current scenario from scratch.
+    Restart,
+    /// Select the previous available scenario.
+    PreviousScenario,
+    /// Select the next available scenario.
+    NextScenario,
+    /// Load the selected scenario.
+    LoadScenario,
+    /// Close the application.
+    Quit,
+}
+
+/// A keyboard-navigated overlay menu shown while the simulation is paused.
+#[derive(Debug, Default)]
+pub struct PauseMenu {
+    /// The index of the currently highlighted entry.
+    selection: usize,
+}
+
+impl PauseMenu {
+    /// Reads the arrow and enter keys to navigate the menu and returns the chosen action, if any.
+    pub fn interact(&mut self) -> Option<MenuAction> {
+        if is_key_pressed(KeyCode::Up) {
+            self.selection = (self.selection + ENTRIES.len() - 1) % ENTRIES.len();
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.selection = (self.selection + 1) % ENTRIES.len();
+        }
+
+        match ENTRIES[self.selection] {
+            MenuEntry::SelectScenario if is_key_pressed(KeyCode::Left) => {
+                Some(MenuAction::PreviousScenario)
+            }
+            MenuEntry::SelectScenario if is_key_pressed(KeyCode::Right) => {
+                Some(MenuAction::NextScenario)
+            }
+            entry if is_key_pressed(KeyCode::Enter) => Some(match entry {
+                MenuEntry::Resume => MenuAction::Resume,
+                MenuEntry::Restart => MenuAction::Restart,
+                MenuEntry::SelectScenario => MenuAction::LoadScenario,
+                MenuEntry::Quit => MenuAction::Quit,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Draws the menu in screen space, showing the name of the selected scenario.
+    pub fn draw(&self, scenario_name: &str) {
+        let (w, h) = (screen_width(), screen_height());
+        draw_rectangle(0., 0., w, h, Color::new(0., 0., 0., 0.6));
+        draw_text("Paused", w / 2. - 48., h / 2. - 72., 32., WHITE);
+
+        for (i, entry) in ENTRIES.iter().enumerate() {
+            let label = match entry {
+                MenuEntry::Resume => "Resume".to_owned(),
+                MenuEntry::Restart => "Restart".to_owned(),
+                MenuEntry::SelectScenario => format!("< Scenario: {scenario_name} >"),
+                MenuEntry::Quit => "Quit".to_owned(),
+            };
+            let color = if i == self.selection { YELLOW } else { GRAY };
+            draw_text(
+                &label,
+                w / 2. - 48.,
+                h / 2. - 32. + 24. * i as f32,
+                20.,
+                color,
+            );
+        }
+    }
+}
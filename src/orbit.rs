@@ -0,0 +1,123 @@
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+
+/// The osculating Keplerian orbit of an object around a primary, relative to the primary's position.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    /// The eccentricity of the orbit. Below 1 the orbit is bound, above it the object escapes.
+    pub eccentricity: f32,
+    /// The point of closest approach to the primary.
+    pub periapsis: Vec2,
+    /// The point of farthest distance from the primary, if the orbit is bound.
+    pub apoapsis: Option<Vec2>,
+    /// The time in ticks one revolution takes, if the orbit is bound.
+    pub period: Option<f32>,
+}
+
+/// The velocity relative to the primary that keeps an object at `offset` from it on a circular orbit, given the standard gravitational parameter `mu`.
+/// Objects orbit in the direction of positive angles.
+pub fn circular_velocity(offset: Vec2, mu: f32) -> Vec2 {
+    let r = offset.length();
+    if r == 0. || mu <= 0. {
+        return Vec2::ZERO;
+    }
+    offset.perp() / r * (mu / r).sqrt()
+}
+
+/// Wether an object is captured by its primary, decided by the sign of its specific orbital energy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitClass {
+    /// The object moves on an ellipse and keeps returning to the primary.
+    Bound,
+    /// The object has just about escape velocity.
+    Parabolic,
+    /// The object moves on a hyperbola and leaves the primary for good.
+    Escaping,
+}
+
+impl OrbitClass {
+    /// The fraction of the potential energy within which the specific energy counts as zero, making the orbit parabolic.
+    const PARABOLIC_TOLERANCE: f32 = 0.01;
+
+    /// Classifies the orbit with the given position and velocity relative to the primary by its specific energy v²/2 − μ/r.
+    /// Returns `None` for an object resting on the primary or a primary without gravity.
+    pub fn from_state(position: Vec2, velocity: Vec2, mu: f32) -> Option<Self> {
+        let r = position.length();
+        if r == 0. || mu <= 0. {
+            return None;
+        }
+        let potential = mu / r;
+        let energy = velocity.length_squared() / 2. - potential;
+        Some(if energy.abs() <= potential * Self::PARABOLIC_TOLERANCE {
+            OrbitClass::Parabolic
+        } else if energy < 0. {
+            OrbitClass::Bound
+        } else {
+            OrbitClass::Escaping
+        })
+    }
+
+    /// The name the class is displayed with.
+    pub fn name(self) -> &'static str {
+        match self {
+            OrbitClass::Bound => "Bound",
+            OrbitClass::Parabolic => "Parabolic",
+            OrbitClass::Escaping => "Escaping",
+        }
+    }
+
+    /// The color orbits of this class are drawn with.
+    pub fn color(self) -> Color {
+        match self {
+            OrbitClass::Bound => GREEN,
+            OrbitClass::Parabolic => YELLOW,
+            OrbitClass::Escaping => RED,
+        }
+    }
+}
+
+impl OrbitalElements {
+    /// Derives the orbital elements from the position and velocity relative to the primary and the standard gravitational parameter `mu`, the gravitational constant times the combined mass.
+    /// Returns `None` for degenerate states, such as an object resting on the primary or moving straight towards it.
+    pub fn from_state(position: Vec2, velocity: Vec2, mu: f32) -> Option<Self> {
+        let r = position.length();
+        // Specific angular momentum, the z-component of the cross product in 2-D
+        let h = position.perp_dot(velocity);
+        if r == 0. || mu <= 0. || h == 0. {
+            return None;
+        }
+
+        // Eccentricity vector, pointing from the primary towards the periapsis
+        let e_vec = ((velocity.length_squared() - mu / r) * position
+            - position.dot(velocity) * velocity)
+            / mu;
+        let eccentricity = e_vec.length();
+        // A circular orbit has no distinguished periapsis, so use the current position
+        let direction = if eccentricity > f32::EPSILON {
+            e_vec / eccentricity
+        } else {
+            position / r
+        };
+
+        // The periapsis distance follows from the angular momentum and holds for all conic sections
+        let periapsis = direction * h * h / (mu * (1. + eccentricity));
+
+        let (apoapsis, period) = if eccentricity < 1. {
+            let semi_major_axis = h * h / (mu * (1. - eccentricity * eccentricity));
+            (
+                Some(-direction * semi_major_axis * (1. + eccentricity)),
+                Some(2. * PI * (semi_major_axis.powi(3) / mu).sqrt()),
+            )
+        } else {
+            (None, None)
+        };
+
+        Some(Self {
+            eccentricity,
+            periapsis,
+            apoapsis,
+            period,
+        })
+    }
+}
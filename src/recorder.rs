@@ -0,0 +1,96 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::space_object::SpaceObject;
+
+/// Which objects are written to a trajectory recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFilter {
+    /// Every object.
+    All,
+    /// Only ships.
+    Ships,
+    /// Only celestial bodies.
+    Bodies,
+    /// Only projectiles.
+    Projectiles,
+}
+
+impl RecordFilter {
+    /// Wether the object is written to the recording.
+    fn matches(self, object: &SpaceObject) -> bool {
+        match self {
+            RecordFilter::All => true,
+            RecordFilter::Ships => object.is_ship(),
+            RecordFilter::Bodies => object.is_body(),
+            RecordFilter::Projectiles => object.is_projectile(),
+        }
+    }
+}
+
+impl FromStr for RecordFilter {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(RecordFilter::All),
+            "ships" => Ok(RecordFilter::Ships),
+            "bodies" => Ok(RecordFilter::Bodies),
+            "projectiles" => Ok(RecordFilter::Projectiles),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Writes the state of objects after every physics step to a CSV file.
+#[derive(Debug)]
+pub struct Recorder {
+    /// The buffered file the rows are written to.
+    writer: BufWriter<File>,
+    /// Which objects are written.
+    filter: RecordFilter,
+    /// The amount of physics steps recorded so far.
+    steps: u64,
+}
+
+impl Recorder {
+    /// Creates the CSV file at `path`, overwriting it if present, and writes the header row.
+    pub fn create(path: &Path, filter: RecordFilter) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "step,sim_time,id,x,y,vx,vy,mass")?;
+        Ok(Self {
+            writer,
+            filter,
+            steps: 0,
+        })
+    }
+
+    /// Writes one row for every object passing the filter, at the simulated time `sim_time`.
+    pub fn record(&mut self, sim_time: f64, objects: &[SpaceObject]) -> std::io::Result<()> {
+        for object in objects.iter().filter(|object| self.filter.matches(object)) {
+            let (position, velocity) = (object.get_position(), object.get_velocity());
+            writeln!(
+                self.writer,
+                "{},{sim_time},{},{},{},{},{},{}",
+                self.steps,
+                object.get_id(),
+                position.x,
+                position.y,
+                velocity.x,
+                velocity.y,
+                object.get_mass()
+            )?;
+        }
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Writes all buffered rows to the file, ending the recording.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
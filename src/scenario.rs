@@ -0,0 +1,1030 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::background::Background;
+use crate::forces::GravityMatrix;
+use crate::space_object::{Durability, FirePattern, GlowParams, Path, SpaceObject};
+use crate::sprites::{SpriteKey, SpriteTextures};
+
+/// Describes the initial state of a simulation.
+///
+/// Scenario files contain one directive per line, empty lines and comments starting with `#` are ignored:
+/// ```text
+/// name Default
+/// gravity 0.1
+/// coulomb 1
+/// ignore_gravity 0 1
+/// players 3
+/// background nebula stretched
+/// ship 256 0 0 0.6 keys=W,A,D,S durability=3
+/// body 0 0 0 0 mass=1024 size=96 sprite=sun durability=indestructible anchored=true
+/// ```
+/// Objects are given by their position and velocity, followed by optional `key=value` pairs.
+/// With `players`, the given ships are replaced by that many ships evenly spaced in a circular orbit around the system's primary.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The name shown when selecting the scenario.
+    pub name: String,
+    /// The gravitational constant, if the scenario overrides the default.
+    pub gravity: Option<f32>,
+    /// The constant of the force between charged objects, if the scenario overrides the default.
+    pub coulomb: Option<f32>,
+    /// Which gravity groups attract each other.
+    pub gravity_matrix: GravityMatrix,
+    /// The objects created when the scenario starts.
+    pub objects: Vec<ObjectSpec>,
+    /// The amount of ships placed symmetrically around the system's primary instead of the given ships, if any.
+    pub players: Option<usize>,
+    /// What is drawn behind the simulation, if the scenario overrides the default black.
+    pub background: Option<Background>,
+    /// The file the scenario was loaded from, if any.
+    pub path: Option<std::path::PathBuf>,
+}
+
+/// Describes a single object of a scenario.
+#[derive(Debug, Clone)]
+pub struct ObjectSpec {
+    /// Wether the object is a ship or a celestial body, and its kind-specific properties.
+    pub kind: ObjectKind,
+    /// The initial position of the object.
+    pub position: Vec2,
+    /// The initial velocity of the object.
+    pub velocity: Vec2,
+    /// The sprite the object is drawn with.
+    pub sprite: SpriteKey,
+    /// The amount of collisions the object can survive.
+    pub durability: Durability,
+    /// The color the object's sprite is tinted with.
+    pub tint: Color,
+    /// The electric charge of the object.
+    pub charge: f32,
+    /// Wether the object is held in place.
+    pub anchored: bool,
+    /// The name displayed above the object, if any.
+    pub label: Option<String>,
+    /// The path followed by the object if it is kinematic.
+    pub path: Option<Path>,
+    /// The pulsing halo drawn around the object, if it glows.
+    pub glow: Option<GlowParams>,
+    /// The collision layers of the object as a bit mask, if it overrides the default layer of its kind.
+    pub collision_layer: Option<u32>,
+    /// The collision layers the object collides with as a bit mask, if it overrides the default of colliding with all.
+    pub collision_mask: Option<u32>,
+    /// The gravity group of the object.
+    pub gravity_group: u8,
+}
+
+/// Describes the kind-specific properties of a scenario object.
+#[derive(Debug, Clone)]
+pub enum ObjectKind {
+    /// A controllable ship.
+    Ship {
+        /// The keys for thrust, turning left, turning right and firing.
+        keymap: [KeyCode; 4],
+        /// The optional keys for reverse thrust, strafing left and strafing right.
+        extended_keymap: Option<[KeyCode; 3]>,
+        /// The optional key held to steer the ship into a circular orbit while the orbit assist is enabled.
+        assist_key: Option<KeyCode>,
+        /// The pattern the ship's weapon fires in.
+        fire_pattern: FirePattern,
+        /// The amount of targets the ship's projectiles pierce before expiring.
+        pierce: u8,
+        /// The multiplier of the recoil the ship experiences when firing.
+        recoil: f32,
+        /// The speed of the ship's projectiles relative to the part of its velocity they inherit.
+        muzzle_speed: f32,
+        /// The fraction of the ship's velocity its projectiles inherit.
+        inheritance: f32,
+    },
+    /// A celestial body.
+    Body {
+        /// The mass of the body.
+        mass: f32,
+        /// The size of the body.
+        size: f32,
+        /// The density the size is derived from instead, if given.
+        density: Option<f32>,
+    },
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_owned(),
+            gravity: None,
+            coulomb: None,
+            gravity_matrix: GravityMatrix::default(),
+            objects: vec![
+                // Ships
+                ObjectSpec {
+                    kind: ObjectKind::Ship {
+                        keymap: [KeyCode::W, KeyCode::A, KeyCode::D, KeyCode::S],
+                        extended_keymap: None,
+                        assist_key: Some(DEFAULT_ASSIST_KEYS[0]),
+                        fire_pattern: FirePattern::Single,
+                        pierce: 1,
+                        recoil: 1.,
+                        muzzle_speed: SpaceObject::DEFAULT_MUZZLE_SPEED,
+                        inheritance: 1.,
+                    },
+                    position: Vec2::new(256.0, 0.0),
+                    velocity: Vec2::new(0.0, 0.6),
+                    sprite: SpriteKey::Ship,
+                    durability: Durability::Collisions(3),
+                    tint: DEFAULT_TINTS[0],
+                    charge: 0.,
+                    anchored: false,
+                    label: None,
+                    path: None,
+                    glow: None,
+                    collision_layer: None,
+                    collision_mask: None,
+                    gravity_group: 0,
+                },
+                ObjectSpec {
+                    kind: ObjectKind::Ship {
+                        keymap: [KeyCode::I, KeyCode::J, KeyCode::L, KeyCode::K],
+                        extended_keymap: None,
+                        assist_key: Some(DEFAULT_ASSIST_KEYS[1]),
+                        fire_pattern: FirePattern::Single,
+                        pierce: 1,
+                        recoil: 1.,
+                        muzzle_speed: SpaceObject::DEFAULT_MUZZLE_SPEED,
+                        inheritance: 1.,
+                    },
+                    position: Vec2::new(-256.0, 0.0),
+                    velocity: Vec2::new(0.0, -0.6),
+                    sprite: SpriteKey::Ship,
+                    durability: Durability::Collisions(3),
+                    tint: DEFAULT_TINTS[1],
+                    charge: 0.,
+                    anchored: false,
+                    label: None,
+                    path: None,
+                    glow: None,
+                    collision_layer: None,
+                    collision_mask: None,
+                    gravity_group: 0,
+                },
+                // Sun
+                ObjectSpec {
+                    kind: ObjectKind::Body {
+                        mass: 1024.,
+                        size: 96.,
+                        density: None,
+                    },
+                    position: Vec2::new(0.0, 0.0),
+                    velocity: Vec2::new(0.0, 0.0),
+                    sprite: SpriteKey::Sun,
+                    durability: Durability::Indestructible,
+                    tint: WHITE,
+                    charge: 0.,
+                    anchored: true,
+                    label: None,
+                    path: None,
+                    glow: Some(GlowParams {
+                        color: Color::new(1.0, 0.7, 0.2, 0.5),
+                        radius: 1.6,
+                        pulse: 0.15,
+                        period: 180.,
+                    }),
+                    collision_layer: None,
+                    collision_mask: None,
+                    gravity_group: 0,
+                },
+            ],
+            players: None,
+            background: None,
+            path: None,
+        }
+    }
+}
+
+impl Scenario {
+    /// The file extension of scenario files.
+    pub const EXTENSION: &'static str = "scn";
+
+    /// Loads the scenario from the file at `path`, naming it after the file unless it names itself.
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut scenario = Self::parse(&text, name)?;
+        scenario.path = Some(path.to_owned());
+        Ok(scenario)
+    }
+
+    /// Loads all scenario files in the directory, sorted by file name.
+    /// Files that cannot be loaded are reported and skipped.
+    pub fn load_all(dir: &str) -> Vec<Self> {
+        let mut paths = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == Self::EXTENSION)
+                })
+                .collect::<Vec<_>>(),
+            Err(err) => {
+                eprintln!("Could not read scenario directory {dir}: {err}");
+                Vec::new()
+            }
+        };
+        paths.sort();
+
+        paths
+            .iter()
+            .filter_map(|path| match Self::load(path) {
+                Ok(scenario) => Some(scenario),
+                Err(err) => {
+                    eprintln!("Could not load scenario {}: {err}", path.display());
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Parses a scenario from its textual description.
+    pub fn parse(text: &str, name: String) -> Result<Self, String> {
+        let mut scenario = Self {
+            name,
+            gravity: None,
+            coulomb: None,
+            gravity_matrix: GravityMatrix::default(),
+            objects: Vec::new(),
+            players: None,
+            background: None,
+            path: None,
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            scenario
+                .apply(line)
+                .map_err(|err| format!("line {}: {err}", number + 1))?;
+        }
+
+        Ok(scenario)
+    }
+
+    /// Applies a single directive to the scenario.
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let mut tokens = line.split_whitespace();
+        let directive = tokens.next().unwrap_or_default();
+
+        match directive {
+            "name" => self.name = tokens.collect::<Vec<_>>().join(" "),
+            "gravity" => {
+                self.gravity = Some(parse_value(
+                    "gravity",
+                    tokens.next().ok_or("missing gravity value")?,
+                )?)
+            }
+            "coulomb" => {
+                self.coulomb = Some(parse_value(
+                    "coulomb",
+                    tokens.next().ok_or("missing coulomb value")?,
+                )?)
+            }
+            "players" => {
+                self.players = Some(parse_value(
+                    "players",
+                    tokens.next().ok_or("missing player count")?,
+                )?)
+            }
+            "background" => self.background = Some(parse_background(tokens)?),
+            "ignore_gravity" => {
+                let mut group =
+                    || parse_gravity_group(tokens.next().ok_or("missing gravity group")?);
+                let (a, b) = (group()?, group()?);
+                self.gravity_matrix.set(a, b, false);
+            }
+            "ship" | "body" => {
+                // Ships without explicit keys or tint get those of their player number
+                let player = self
+                    .objects
+                    .iter()
+                    .filter(|spec| matches!(spec.kind, ObjectKind::Ship { .. }))
+                    .count();
+                self.objects
+                    .push(ObjectSpec::parse(directive, player, tokens)?)
+            }
+            _ => return Err(format!("unknown directive `{directive}`")),
+        }
+
+        Ok(())
+    }
+
+    /// Creates a scenario describing the current state of the objects. Projectiles are not included.
+    pub fn from_objects(name: String, gravity: f32, coulomb: f32, objects: &[SpaceObject]) -> Self {
+        Self {
+            name,
+            gravity: Some(gravity),
+            coulomb: Some(coulomb),
+            gravity_matrix: GravityMatrix::default(),
+            objects: objects.iter().filter_map(ObjectSpec::from_object).collect(),
+            players: None,
+            background: None,
+            path: None,
+        }
+    }
+
+    /// Writes this scenario to the file at `path` in the scenario file format.
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Creates the objects of this scenario, drawing their sprites from the sprite cache.
+    pub fn build(
+        &self,
+        sprites: &HashMap<SpriteKey, SpriteTextures>,
+    ) -> Result<Vec<SpaceObject>, String> {
+        self.objects
+            .iter()
+            .map(|spec| spec.build(sprites))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Scenario {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "name {}", self.name)?;
+        if let Some(gravity) = self.gravity {
+            writeln!(f, "gravity {gravity}")?;
+        }
+        if let Some(coulomb) = self.coulomb {
+            writeln!(f, "coulomb {coulomb}")?;
+        }
+        for (a, b) in self.gravity_matrix.ignored_pairs() {
+            writeln!(f, "ignore_gravity {a} {b}")?;
+        }
+        if let Some(players) = self.players {
+            writeln!(f, "players {players}")?;
+        }
+        if let Some(background) = &self.background {
+            writeln!(f, "background {background}")?;
+        }
+        for spec in self.objects.iter() {
+            writeln!(f, "{spec}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ObjectSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let directive = match self.kind {
+            ObjectKind::Ship { .. } => "ship",
+            ObjectKind::Body { .. } => "body",
+        };
+        write!(
+            f,
+            "{directive} {} {} {} {} sprite={}",
+            self.position.x,
+            self.position.y,
+            self.velocity.x,
+            self.velocity.y,
+            self.sprite.name()
+        )?;
+        write!(
+            f,
+            " tint={},{},{},{}",
+            self.tint.r, self.tint.g, self.tint.b, self.tint.a
+        )?;
+        match self.durability {
+            Durability::Indestructible => write!(f, " durability=indestructible")?,
+            Durability::Collisions(c) => write!(f, " durability={c}")?,
+        }
+        if self.charge != 0. {
+            write!(f, " charge={}", self.charge)?;
+        }
+        if self.anchored {
+            write!(f, " anchored=true")?;
+        }
+        if let Some(label) = &self.label {
+            write!(f, " label={}", escape_label(label))?;
+        }
+        if let Some(layer) = self.collision_layer {
+            write!(f, " layer={layer}")?;
+        }
+        if let Some(mask) = self.collision_mask {
+            write!(f, " mask={mask}")?;
+        }
+        if self.gravity_group != 0 {
+            write!(f, " gravity_group={}", self.gravity_group)?;
+        }
+        match &self.path {
+            Some(Path::Circle {
+                center,
+                radius,
+                period,
+            }) => write!(
+                f,
+                " path=circle:{}:{}:{radius}:{period}",
+                center.x, center.y
+            )?,
+            Some(Path::Waypoints { points, speed }) => {
+                write!(f, " path=loop:{speed}")?;
+                for point in points {
+                    write!(f, ":{},{}", point.x, point.y)?;
+                }
+            }
+            None => {}
+        }
+        if let Some(glow) = &self.glow {
+            write!(
+                f,
+                " glow={}:{}:{}:{},{},{},{}",
+                glow.radius,
+                glow.pulse,
+                glow.period,
+                glow.color.r,
+                glow.color.g,
+                glow.color.b,
+                glow.color.a
+            )?;
+        }
+        match &self.kind {
+            ObjectKind::Ship {
+                keymap,
+                extended_keymap,
+                assist_key,
+                fire_pattern,
+                pierce,
+                recoil,
+                muzzle_speed,
+                inheritance,
+            } => {
+                write!(f, " keys={}", key_names(keymap))?;
+                if *pierce != 1 {
+                    write!(f, " pierce={pierce}")?;
+                }
+                if *recoil != 1. {
+                    write!(f, " recoil={recoil}")?;
+                }
+                if *muzzle_speed != SpaceObject::DEFAULT_MUZZLE_SPEED {
+                    write!(f, " muzzle={muzzle_speed}")?;
+                }
+                if *inheritance != 1. {
+                    write!(f, " inherit={inheritance}")?;
+                }
+                if let Some(keys) = extended_keymap {
+                    write!(f, " extended_keys={}", key_names(keys))?;
+                }
+                match assist_key {
+                    Some(key) => write!(f, " assist={}", key_names(&[*key]))?,
+                    None => write!(f, " assist=none")?,
+                }
+                match fire_pattern {
+                    FirePattern::Single => {}
+                    FirePattern::Spread { count, arc } => write!(f, " fire=spread:{count}:{arc}")?,
+                    FirePattern::Burst { count, interval } => {
+                        write!(f, " fire=burst:{count}:{interval}")?
+                    }
+                }
+            }
+            ObjectKind::Body {
+                mass,
+                size,
+                density,
+            } => {
+                write!(f, " mass={mass} size={size}")?;
+                if let Some(density) = density {
+                    write!(f, " density={density}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ObjectSpec {
+    /// Describes the current state of a ship or celestial body. Returns `None` for projectiles.
+    pub fn from_object(object: &SpaceObject) -> Option<Self> {
+        if object.is_projectile() {
+            return None;
+        }
+        // Only describe collision filters differing from the defaults of the kind
+        let (layer, mask) = object.get_collision_filter();
+        let default_layer = if object.is_ship() {
+            SpaceObject::LAYER_SHIPS
+        } else {
+            SpaceObject::LAYER_BODIES
+        };
+        let kind = match object.get_keymaps() {
+            Some((keymap, extended_keymap)) => {
+                let (muzzle_speed, inheritance) = object
+                    .get_muzzle()
+                    .unwrap_or((SpaceObject::DEFAULT_MUZZLE_SPEED, 1.));
+                ObjectKind::Ship {
+                    keymap,
+                    extended_keymap,
+                    assist_key: object.get_assist_key(),
+                    fire_pattern: object.get_fire_pattern().unwrap_or(FirePattern::Single),
+                    pierce: object.get_pierce().unwrap_or(1),
+                    recoil: object.get_recoil().unwrap_or(1.),
+                    muzzle_speed,
+                    inheritance,
+                }
+            }
+            None => ObjectKind::Body {
+                mass: object.get_mass(),
+                size: object.get_size(),
+                density: None,
+            },
+        };
+        Some(Self {
+            kind,
+            position: object.get_position(),
+            velocity: object.get_velocity(),
+            sprite: object.get_sprite_key(),
+            durability: object.get_durability(),
+            tint: object.get_tint(),
+            charge: object.get_charge(),
+            anchored: object.is_anchored(),
+            label: object.get_label().map(str::to_owned),
+            path: object.get_path().cloned(),
+            glow: object.get_glow(),
+            collision_layer: (layer != default_layer).then_some(layer),
+            collision_mask: (mask != SpaceObject::LAYER_ALL).then_some(mask),
+            gravity_group: object.get_gravity_group(),
+        })
+    }
+
+    /// A ship with the default keymap, assist key and tint of the given player number.
+    pub fn player_ship(player: usize, position: Vec2, velocity: Vec2) -> Self {
+        Self {
+            kind: ObjectKind::Ship {
+                keymap: DEFAULT_KEYMAPS[player % DEFAULT_KEYMAPS.len()],
+                extended_keymap: None,
+                assist_key: Some(DEFAULT_ASSIST_KEYS[player % DEFAULT_ASSIST_KEYS.len()]),
+                fire_pattern: FirePattern::Single,
+                pierce: 1,
+                recoil: 1.,
+                muzzle_speed: SpaceObject::DEFAULT_MUZZLE_SPEED,
+                inheritance: 1.,
+            },
+            position,
+            velocity,
+            sprite: SpriteKey::Ship,
+            durability: Durability::Collisions(3),
+            tint: DEFAULT_TINTS[player % DEFAULT_TINTS.len()],
+            charge: 0.,
+            anchored: false,
+            label: None,
+            path: None,
+            glow: None,
+            collision_layer: None,
+            collision_mask: None,
+            gravity_group: 0,
+        }
+    }
+
+    /// Parses an object from the tokens following its `ship` or `body` directive.
+    /// Ships default to the keymap and tint of the given player number.
+    fn parse<'a>(
+        directive: &str,
+        player: usize,
+        mut tokens: impl Iterator<Item = &'a str>,
+    ) -> Result<Self, String> {
+        let mut numbers = [0.; 4];
+        for (number, name) in numbers.iter_mut().zip(["x", "y", "vx", "vy"]) {
+            *number = parse_value(name, tokens.next().ok_or(format!("missing {name}"))?)?;
+        }
+
+        let mut spec = if directive == "ship" {
+            Self::player_ship(
+                player,
+                Vec2::new(numbers[0], numbers[1]),
+                Vec2::new(numbers[2], numbers[3]),
+            )
+        } else {
+            Self {
+                kind: ObjectKind::Body {
+                    mass: 1.,
+                    size: 16.,
+                    density: None,
+                },
+                position: Vec2::new(numbers[0], numbers[1]),
+                velocity: Vec2::new(numbers[2], numbers[3]),
+                sprite: SpriteKey::Earth,
+                durability: Durability::Indestructible,
+                tint: WHITE,
+                charge: 0.,
+                anchored: false,
+                label: None,
+                path: None,
+                glow: None,
+                collision_layer: None,
+                collision_mask: None,
+                gravity_group: 0,
+            }
+        };
+
+        for option in tokens {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key=value`, found `{option}`"))?;
+            spec.apply(key, value)?;
+        }
+
+        Ok(spec)
+    }
+
+    /// Applies a single `key=value` option to the object.
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match (key, &mut self.kind) {
+            ("sprite", _) => {
+                self.sprite = SpriteKey::from_name(value)
+                    .ok_or_else(|| format!("unknown sprite `{value}`"))?
+            }
+            ("tint", _) => self.tint = parse_color(value)?,
+            ("charge", _) => self.charge = parse_value(key, value)?,
+            ("anchored", _) => self.anchored = parse_value(key, value)?,
+            ("label", _) => self.label = Some(unescape_label(value)),
+            ("path", _) => self.path = Some(parse_path(value)?),
+            ("glow", _) => self.glow = Some(parse_glow(value)?),
+            ("layer", _) => self.collision_layer = Some(parse_value(key, value)?),
+            ("mask", _) => self.collision_mask = Some(parse_value(key, value)?),
+            ("gravity_group", _) => self.gravity_group = parse_gravity_group(value)?,
+            ("durability", _) => {
+                self.durability = if value == "indestructible" {
+                    Durability::Indestructible
+                } else {
+                    Durability::Collisions(parse_value(key, value)?)
+                }
+            }
+            ("keys", ObjectKind::Ship { keymap, .. }) => *keymap = parse_keys(value)?,
+            (
+                "extended_keys",
+                ObjectKind::Ship {
+                    extended_keymap, ..
+                },
+            ) => *extended_keymap = Some(parse_keys(value)?),
+            ("assist", ObjectKind::Ship { assist_key, .. }) => {
+                *assist_key = if value == "none" {
+                    None
+                } else {
+                    Some(parse_key(value).ok_or_else(|| format!("unknown key `{value}`"))?)
+                }
+            }
+            ("fire", ObjectKind::Ship { fire_pattern, .. }) => {
+                *fire_pattern = parse_fire_pattern(value)?
+            }
+            ("pierce", ObjectKind::Ship { pierce, .. }) => *pierce = parse_value(key, value)?,
+            ("recoil", ObjectKind::Ship { recoil, .. }) => *recoil = parse_value(key, value)?,
+            ("muzzle", ObjectKind::Ship { muzzle_speed, .. }) => {
+                *muzzle_speed = parse_value(key, value)?
+            }
+            ("inherit", ObjectKind::Ship { inheritance, .. }) => {
+                *inheritance = parse_value(key, value)?
+            }
+            ("mass", ObjectKind::Body { mass, .. }) => *mass = parse_value(key, value)?,
+            ("size", ObjectKind::Body { size, .. }) => *size = parse_value(key, value)?,
+            ("density", ObjectKind::Body { density, .. }) => {
+                *density = Some(parse_value(key, value)?)
+            }
+            _ => return Err(format!("unknown option `{key}`")),
+        }
+        Ok(())
+    }
+
+    /// Creates the described object, drawing its sprite from the sprite cache.
+    pub fn build(
+        &self,
+        sprites: &HashMap<SpriteKey, SpriteTextures>,
+    ) -> Result<SpaceObject, String> {
+        let textures = sprites
+            .get(&self.sprite)
+            .ok_or_else(|| format!("missing sprite `{}`", self.sprite.name()))?;
+
+        let object = match self.kind {
+            ObjectKind::Ship {
+                keymap,
+                extended_keymap,
+                assist_key,
+                fire_pattern,
+                pierce,
+                recoil,
+                muzzle_speed,
+                inheritance,
+            } => {
+                let ship = SpaceObject::ship(
+                    self.position,
+                    self.velocity,
+                    self.sprite,
+                    textures,
+                    keymap,
+                    self.durability,
+                )
+                .with_fire_pattern(fire_pattern)
+                .with_pierce(pierce)
+                .with_recoil(recoil)
+                .with_muzzle(muzzle_speed, inheritance)
+                .with_assist_key(assist_key);
+                match extended_keymap {
+                    Some(keys) => ship.with_extended_controls(keys),
+                    None => ship,
+                }
+            }
+            ObjectKind::Body {
+                mass,
+                size,
+                density,
+            } => {
+                let body = SpaceObject::body(
+                    self.position,
+                    self.velocity,
+                    mass,
+                    size,
+                    self.sprite,
+                    textures,
+                    self.durability,
+                );
+                match density {
+                    Some(density) => body.with_density(density),
+                    None => body,
+                }
+            }
+        };
+        Ok(object
+            .with_tint(self.tint)
+            .with_charge(self.charge)
+            .with_anchored(self.anchored)
+            .with_label(self.label.clone())
+            .with_path(self.path.clone())
+            .with_glow(self.glow)
+            .with_collision_filter(self.collision_layer, self.collision_mask)
+            .with_gravity_group(self.gravity_group))
+    }
+}
+
+/// Parses a comma-separated list of key names.
+fn parse_keys<const N: usize>(value: &str) -> Result<[KeyCode; N], String> {
+    let keys = value
+        .split(',')
+        .map(|name| parse_key(name.trim()).ok_or_else(|| format!("unknown key `{name}`")))
+        .collect::<Result<Vec<_>, _>>()?;
+    keys.try_into()
+        .map_err(|keys: Vec<_>| format!("expected {N} keys, found {}", keys.len()))
+}
+
+/// Joins the names of the keys to a comma-separated list.
+fn key_names(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .map(|&key| {
+            KEY_NAMES
+                .iter()
+                .find(|&&(_, named_key)| named_key == key)
+                .map_or("?", |&(name, _)| name)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The keymaps assigned to ships in order, used when ships are created without an explicit keymap.
+pub const DEFAULT_KEYMAPS: [[KeyCode; 4]; 4] = [
+    [KeyCode::W, KeyCode::A, KeyCode::D, KeyCode::S],
+    [KeyCode::I, KeyCode::J, KeyCode::L, KeyCode::K],
+    [KeyCode::Up, KeyCode::Left, KeyCode::Right, KeyCode::Down],
+    [KeyCode::Kp8, KeyCode::Kp4, KeyCode::Kp6, KeyCode::Kp5],
+];
+
+/// The keys held to use the orbit assist assigned to ships in order, next to the default keymaps and clear of all global hotkeys.
+pub const DEFAULT_ASSIST_KEYS: [KeyCode; 4] = [
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::RightControl,
+    KeyCode::Kp0,
+];
+
+/// The tints assigned to ships in order, used when ships are created without an explicit tint.
+pub const DEFAULT_TINTS: [Color; 4] = [
+    Color::new(0.6, 0.8, 1.0, 1.0),
+    Color::new(1.0, 0.6, 0.6, 1.0),
+    Color::new(0.6, 1.0, 0.6, 1.0),
+    Color::new(1.0, 0.9, 0.5, 1.0),
+];
+
+/// Parses a fire pattern given as `single`, `spread:<count>:<arc>` or `burst:<count>:<interval>`.
+fn parse_fire_pattern(value: &str) -> Result<FirePattern, String> {
+    let mut parts = value.split(':');
+    let pattern = match (parts.next(), parts.next(), parts.next()) {
+        (Some("single"), None, None) => FirePattern::Single,
+        (Some("spread"), Some(count), Some(arc)) => FirePattern::Spread {
+            count: parse_value("fire", count)?,
+            arc: parse_value("fire", arc)?,
+        },
+        (Some("burst"), Some(count), Some(interval)) => FirePattern::Burst {
+            count: parse_value("fire", count)?,
+            interval: parse_value("fire", interval)?,
+        },
+        _ => return Err(format!("invalid fire pattern `{value}`")),
+    };
+    match parts.next() {
+        Some(_) => Err(format!("invalid fire pattern `{value}`")),
+        None => Ok(pattern),
+    }
+}
+
+/// Parses a kinematic path given as `circle:<center x>:<center y>:<radius>:<period>` or `loop:<speed>:<x>,<y>:<x>,<y>...`.
+fn parse_path(value: &str) -> Result<Path, String> {
+    let mut parts = value.split(':');
+    match parts.next() {
+        Some("circle") => {
+            let mut numbers = [0.; 4];
+            for number in numbers.iter_mut() {
+                *number = parse_value("path", parts.next().ok_or("incomplete circle path")?)?;
+            }
+            if parts.next().is_some() {
+                return Err(format!("invalid path `{value}`"));
+            }
+            Ok(Path::Circle {
+                center: Vec2::new(numbers[0], numbers[1]),
+                radius: numbers[2],
+                period: numbers[3],
+            })
+        }
+        Some("loop") => {
+            let speed = parse_value("path", parts.next().ok_or("missing loop speed")?)?;
+            let points = parts
+                .map(|point| {
+                    let (x, y) = point
+                        .split_once(',')
+                        .ok_or_else(|| format!("invalid waypoint `{point}`"))?;
+                    Ok(Vec2::new(parse_value("path", x)?, parse_value("path", y)?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            if points.is_empty() {
+                return Err("loop path without waypoints".to_owned());
+            }
+            Ok(Path::Waypoints { points, speed })
+        }
+        _ => Err(format!("invalid path `{value}`")),
+    }
+}
+
+/// Parses a glow given as `<radius>:<pulse>:<period>:<r>,<g>,<b>[,<a>]`.
+fn parse_glow(value: &str) -> Result<GlowParams, String> {
+    let parts = value.split(':').collect::<Vec<_>>();
+    match parts[..] {
+        [radius, pulse, period, color] => Ok(GlowParams {
+            color: parse_color(color)?,
+            radius: parse_value("glow", radius)?,
+            pulse: parse_value("glow", pulse)?,
+            period: parse_value("glow", period)?,
+        }),
+        _ => Err(format!("invalid glow `{value}`")),
+    }
+}
+
+/// Writes a label as a single token: labels cannot contain whitespace in the file, so underscores stand in for spaces.
+/// Underscores and backslashes of the label itself are escaped with a backslash, so [`unescape_label`] restores it exactly.
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            ' ' => escaped.push('_'),
+            '_' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reads a label written by [`escape_label`], turning underscores into spaces unless escaped with a backslash.
+fn unescape_label(value: &str) -> String {
+    let mut label = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '_' => label.push(' '),
+            '\\' => label.push(chars.next().unwrap_or('\\')),
+            _ => label.push(c),
+        }
+    }
+    label
+}
+
+/// Parses the number of a gravity group, which must be below [`GravityMatrix::GROUPS`].
+fn parse_gravity_group(value: &str) -> Result<u8, String> {
+    let group: u8 = parse_value("gravity group", value)?;
+    if group as usize >= GravityMatrix::GROUPS {
+        return Err(format!(
+            "gravity group {group} out of range, expected below {}",
+            GravityMatrix::GROUPS
+        ));
+    }
+    Ok(group)
+}
+
+/// Parses a background given as either a color or an image name, optionally followed by `tiled` or `stretched`.
+fn parse_background<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Background, String> {
+    let value = tokens.next().ok_or("missing background")?;
+    if value.contains(',') {
+        return Ok(Background::Color(parse_color(value)?));
+    }
+    let tiled = match tokens.next() {
+        None | Some("stretched") => false,
+        Some("tiled") => true,
+        Some(mode) => return Err(format!("unknown background mode `{mode}`")),
+    };
+    Ok(Background::Image {
+        name: value.to_owned(),
+        tiled,
+    })
+}
+
+/// Parses a color from its comma-separated red, green, blue and optional alpha components between 0 and 1.
+fn parse_color(value: &str) -> Result<Color, String> {
+    let components = value
+        .split(',')
+        .map(|component| parse_value("tint", component.trim()))
+        .collect::<Result<Vec<f32>, _>>()?;
+    match components[..] {
+        [r, g, b] => Ok(Color::new(r, g, b, 1.0)),
+        [r, g, b, a] => Ok(Color::new(r, g, b, a)),
+        _ => Err(format!("expected 3 or 4 color components, found `{value}`")),
+    }
+}
+
+/// The names of all keys that can be used in scenario keymaps.
+const KEY_NAMES: [(&str, KeyCode); 53] = [
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("LeftAlt", KeyCode::LeftAlt),
+    ("RightAlt", KeyCode::RightAlt),
+    ("RightControl", KeyCode::RightControl),
+    ("Kp0", KeyCode::Kp0),
+    ("Kp1", KeyCode::Kp1),
+    ("Kp2", KeyCode::Kp2),
+    ("Kp4", KeyCode::Kp4),
+    ("Kp5", KeyCode::Kp5),
+    ("Kp6", KeyCode::Kp6),
+    ("Kp8", KeyCode::Kp8),
+    ("Kp9", KeyCode::Kp9),
+];
+
+/// Looks up a key by its name in scenario files.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|&(_, key)| key)
+}
+
+/// Parses a value of a scenario directive, describing the offending key on failure.
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{key}`"))
+}
@@ -0,0 +1,179 @@
+use macroquad::prelude::*;
+
+use crate::recorder::RecordFilter;
+
+/// Settings read from a file before the window is opened.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// The initial width of the window.
+    pub width: i32,
+    /// The initial height of the window.
+    pub height: i32,
+    /// The title of the window.
+    pub title: String,
+    /// Wether the window starts in fullscreen mode.
+    pub fullscreen: bool,
+    /// The directory sprites are loaded from.
+    pub assets_dir: String,
+    /// The minimum world units per pixel the auto-framing camera zooms in to.
+    pub min_camera_scale: f32,
+    /// The maximum world units per pixel the auto-framing camera zooms out to.
+    pub max_camera_scale: f32,
+    /// The distance per square root of an attractor's mass beyond which it exerts no gravity.
+    pub gravity_cutoff: f32,
+    /// The mass below which objects exert no gravity.
+    pub gravity_min_mass: f32,
+    /// The amount of collisions a ship loses when ramming another ship with ship bouncing enabled.
+    pub ship_ram_damage: u8,
+    /// Wether the length of physics steps adapts to the speed and separation of objects.
+    pub adaptive_timestep: bool,
+    /// The amount of objects above which the oldest projectiles are removed when new ones are fired.
+    pub max_objects: usize,
+    /// The amount of most recent physics steps the simulation can be rewound by.
+    pub history_length: usize,
+    /// The amount of substeps every physics step is divided into, between 1 and 8.
+    pub substeps: usize,
+    /// The time in ticks after a collision before the same pair of objects can collide again.
+    pub collision_cooldown: f32,
+    /// The CSV file trajectories are recorded to.
+    pub record_path: String,
+    /// Which objects are written to trajectory recordings.
+    pub record_filter: RecordFilter,
+    /// Wether recording trajectories starts right away.
+    pub record: bool,
+    /// The amount of ships placed symmetrically in every scenario instead of its own ships, if any.
+    pub players: Option<usize>,
+    /// The time in ticks after spawning during which ships take no collision damage.
+    pub spawn_protection: f32,
+    /// The speed no object moves faster than.
+    pub max_speed: f32,
+    /// Wether holding its assist key steers a ship towards a circular orbit.
+    pub orbit_assist: bool,
+    /// The factor the camera shake on impacts is scaled by, 0 disables it.
+    pub screen_shake: f32,
+    /// Wether objects overlapping when a scenario starts are pushed apart instead of only reported.
+    pub separate_overlaps: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            title: "Orbits".to_owned(),
+            fullscreen: false,
+            assets_dir: "assets".to_owned(),
+            min_camera_scale: 0.5,
+            max_camera_scale: 4.0,
+            gravity_cutoff: f32::INFINITY,
+            gravity_min_mass: 0.0,
+            ship_ram_damage: 1,
+            adaptive_timestep: false,
+            max_objects: 512,
+            history_length: 600,
+            substeps: 1,
+            collision_cooldown: 30.0,
+            record_path: "trajectories.csv".to_owned(),
+            record_filter: RecordFilter::All,
+            record: false,
+            players: None,
+            spawn_protection: 120.0,
+            max_speed: f32::INFINITY,
+            orbit_assist: false,
+            screen_shake: 1.0,
+            separate_overlaps: false,
+        }
+    }
+}
+
+impl Settings {
+    /// The path the settings file is read from.
+    pub const PATH: &'static str = "settings.cfg";
+
+    /// Loads the settings from the file at `path`.
+    /// Falls back to the default settings if the file is missing, and to the default value of every key that is malformed.
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("Could not read settings file {path}: {err}");
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses settings from lines of `key = value` pairs, ignoring empty lines and comments starting with `#`.
+    fn parse(text: &str) -> Self {
+        let mut settings = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Err(err) = settings.apply(line) {
+                eprintln!("Ignoring line {} of settings file: {err}", number + 1);
+            }
+        }
+
+        settings
+    }
+
+    /// Applies a single `key = value` line to the settings.
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `key = value`, found `{line}`"))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        match key {
+            "width" => self.width = parse_value(key, value)?,
+            "height" => self.height = parse_value(key, value)?,
+            "title" => self.title = value.to_owned(),
+            "fullscreen" => self.fullscreen = parse_value(key, value)?,
+            "assets_dir" => self.assets_dir = value.to_owned(),
+            "min_camera_scale" => self.min_camera_scale = parse_value(key, value)?,
+            "max_camera_scale" => self.max_camera_scale = parse_value(key, value)?,
+            "gravity_cutoff" => self.gravity_cutoff = parse_value(key, value)?,
+            "gravity_min_mass" => self.gravity_min_mass = parse_value(key, value)?,
+            "ship_ram_damage" => self.ship_ram_damage = parse_value(key, value)?,
+            "adaptive_timestep" => self.adaptive_timestep = parse_value(key, value)?,
+            "max_objects" => self.max_objects = parse_value(key, value)?,
+            "history_length" => self.history_length = parse_value(key, value)?,
+            "substeps" => self.substeps = parse_value(key, value)?,
+            "collision_cooldown" => self.collision_cooldown = parse_value(key, value)?,
+            "record_path" => self.record_path = value.to_owned(),
+            "record_filter" => self.record_filter = parse_value(key, value)?,
+            "record" => self.record = parse_value(key, value)?,
+            "players" => self.players = Some(parse_value(key, value)?),
+            "spawn_protection" => self.spawn_protection = parse_value(key, value)?,
+            "max_speed" => self.max_speed = parse_value(key, value)?,
+            "orbit_assist" => self.orbit_assist = parse_value(key, value)?,
+            "screen_shake" => self.screen_shake = parse_value(key, value)?,
+            "separate_overlaps" => self.separate_overlaps = parse_value(key, value)?,
+            _ => return Err(format!("unknown key `{key}`")),
+        }
+
+        Ok(())
+    }
+
+    /// Creates the window configuration described by these settings.
+    pub fn window_conf(&self) -> Conf {
+        Conf {
+            window_title: self.title.clone(),
+            window_width: self.width,
+            window_height: self.height,
+            fullscreen: self.fullscreen,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parses the value of a setting, describing the offending key on failure.
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{key}`"))
+}
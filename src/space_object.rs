@@ -1,24 +1,230 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use macroquad::prelude::*;
 
+use crate::sprites::{SpriteKey, SpriteTextures};
+
+/// The id given to the next created space object.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new id, distinct from all ids returned before.
+fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 /// Describes a physical object in space
 #[derive(Debug, Clone)]
 pub struct SpaceObject {
+    /// Stable id identifying the object, independent of its place in any list.
+    id: u64,
     /// 2-D position vector of the object.
     position: Vec2,
     /// 2-D velocity vector of the object.
     velocity: Vec2,
     /// Angle the object is facing, with respect to an (1,0) x-axis vector.
     angle: f32,
+    /// The position of the object at the start of the current physics tick, used to interpolate drawing.
+    previous_position: Vec2,
+    /// The angle of the object at the start of the current physics tick, used to interpolate drawing.
+    previous_angle: f32,
     /// The mass of the object, determining its gravitational properties.
     mass: f32,
     /// The size of the object, determining its collision and appearance.
     size: f32,
     /// The image drawn to represent the object.
     sprite: Texture2D,
+    /// Textures of the sprite in other resolutions, sorted by width.
+    variants: Vec<Texture2D>,
+    /// The sprite the object was created with.
+    sprite_key: SpriteKey,
+    /// The color the sprite is tinted with.
+    tint: Color,
     /// If the objects is a controllable space ship, this contains the ships special properties.
     ship: Option<ShipInfo>,
     /// Amount of collisions with other objects this one can survive
-    collisions: Option<u8>,
+    durability: Durability,
+    /// The id of the ship that fired this object, if it is a projectile.
+    owner: Option<u64>,
+    /// The electric charge of the object. Like charges repel and opposite charges attract.
+    charge: f32,
+    /// Wether the object is held in place, still attracting and colliding with others but never moved by forces or collisions.
+    anchored: bool,
+    /// The ids of the objects this projectile already hit, so piercing projectiles damage every target only once.
+    hits: Vec<u64>,
+    /// The name displayed above the object, if any.
+    label: Option<String>,
+    /// The prescribed path of a kinematic object, which moves along it instead of following forces.
+    path: Option<Path>,
+    /// The time the object has been following its path for.
+    path_time: f32,
+    /// The angle at which the object joined a circular path.
+    path_phase: f32,
+    /// The pulsing halo drawn around the object, if it glows.
+    glow: Option<GlowParams>,
+    /// The remaining simulated time the object is drawn flashing white after being hit.
+    flash_timer: f32,
+    /// The collision layers the object is on, as a bit mask.
+    collision_layer: u32,
+    /// The collision layers the object can collide with, as a bit mask.
+    collision_mask: u32,
+    /// The group deciding which other objects this one attracts and is attracted by.
+    gravity_group: u8,
+}
+
+/// Describes how many collisions with other objects a space object can survive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    /// The object is never destroyed by collisions.
+    Indestructible,
+    /// The object can survive this many more collisions and is destroyed once none are left.
+    Collisions(u8),
+}
+
+/// Describes how collisions between space objects are resolved.
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionRules {
+    /// Wether projectiles ricochet off celestial bodies instead of being destroyed by them.
+    pub bounce_off_bodies: bool,
+    /// The fraction of their speed projectiles keep when ricocheting off bodies.
+    pub restitution: f32,
+    /// Wether colliding ships bounce off each other elastically instead of mutually losing a collision.
+    pub ship_bounce: bool,
+    /// The amount of collisions a ship loses when bouncing off another ship.
+    pub ship_ram_damage: u8,
+    /// Wether projectiles can collide with the ship that fired them.
+    pub friendly_fire: bool,
+    /// Wether collisions are switched off entirely, letting all objects pass through each other.
+    pub disabled: bool,
+    /// Wether ships take no collision damage.
+    pub invincible_ships: bool,
+}
+
+/// Describes which projectiles a ship's weapon emits when firing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirePattern {
+    /// A single projectile along the ship's facing.
+    Single,
+    /// Several projectiles at once, fanned out evenly across an arc around the ship's facing.
+    Spread {
+        /// The amount of projectiles.
+        count: u8,
+        /// The angle in radians the projectiles are spread across.
+        arc: f32,
+    },
+    /// A rapid sequence of projectiles, one after another.
+    Burst {
+        /// The amount of projectiles.
+        count: u8,
+        /// The time between two projectiles.
+        interval: f32,
+    },
+}
+
+/// The changing physical and gameplay state of a space object at one point in time, used to rewind it.
+#[derive(Debug, Clone)]
+pub struct ObjectState {
+    /// The id of the object this state belongs to.
+    pub id: u64,
+    position: Vec2,
+    velocity: Vec2,
+    angle: f32,
+    mass: f32,
+    durability: Durability,
+    path_time: f32,
+    flash_timer: f32,
+    hits: Vec<u64>,
+    /// Fuel, cooldowns, spawn protection and flight record of a ship.
+    ship: Option<ShipInfo>,
+}
+
+/// A prescribed path a kinematic object follows as a function of time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    /// A circle run through counterclockwise at constant speed.
+    Circle {
+        /// The center of the circle.
+        center: Vec2,
+        /// The radius of the circle.
+        radius: f32,
+        /// The time one revolution takes.
+        period: f32,
+    },
+    /// A closed loop through a list of points, starting at the first one, run through at constant speed.
+    Waypoints {
+        /// The points of the loop, the last one connecting back to the first.
+        points: Vec<Vec2>,
+        /// The distance covered per unit of time.
+        speed: f32,
+    },
+}
+
+impl Path {
+    /// Returns the position and velocity on the path at `time`, with circles starting at the angle `phase`.
+    fn state_at(&self, time: f32, phase: f32) -> (Vec2, Vec2) {
+        match self {
+            Path::Circle {
+                center,
+                radius,
+                period,
+            } => {
+                let angular_speed = std::f32::consts::TAU / period;
+                let direction = Vec2::from_angle(phase + angular_speed * time);
+                (
+                    *center + direction * *radius,
+                    direction.perp() * *radius * angular_speed,
+                )
+            }
+            Path::Waypoints { points, speed } => {
+                let segments = points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(&from, &to)| (from, to));
+                let length = segments
+                    .clone()
+                    .map(|(from, to)| from.distance(to))
+                    .sum::<f32>();
+                if length <= 0. {
+                    return (points.first().copied().unwrap_or_default(), Vec2::ZERO);
+                }
+                // Walk along the loop until the segment containing the covered distance is found
+                let mut remaining = (speed * time).rem_euclid(length);
+                for (from, to) in segments {
+                    let segment = from.distance(to);
+                    if remaining <= segment && segment > 0. {
+                        let direction = (to - from) / segment;
+                        return (from + direction * remaining, direction * *speed);
+                    }
+                    remaining -= segment;
+                }
+                (points[0], Vec2::ZERO)
+            }
+        }
+    }
+}
+
+/// Describes the pulsing halo drawn behind a glowing object such as a star.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlowParams {
+    /// The color of the halo, its alpha being the opacity at the object's surface.
+    pub color: Color,
+    /// The radius of the halo relative to the radius of the object.
+    pub radius: f32,
+    /// The fraction by which the halo's radius and opacity oscillate.
+    pub pulse: f32,
+    /// The simulated time one pulse takes.
+    pub period: f32,
+}
+
+/// Records the best gravity assist achievements of a ship.
+#[derive(Debug, Clone, Copy)]
+pub struct FlightRecord {
+    /// The closest the ship came to the surface of the target body.
+    pub closest_approach: f32,
+    /// The highest speed the ship reached.
+    pub top_speed: f32,
+    /// Points awarded for skimming the target body without colliding with it.
+    pub skim_score: f32,
 }
 
 /// Describes properties of a space object that is also a ship.
@@ -28,49 +234,361 @@ struct ShipInfo {
     shot_cd: f32,
     /// The keymap used to control the ship.
     keymap: [KeyCode; 4],
+    /// The optional keys for reverse thrust, strafing left and strafing right.
+    extended_keymap: Option<[KeyCode; 3]>,
+    /// The optional key held to steer the ship into a circular orbit while the orbit assist is enabled.
+    assist_key: Option<KeyCode>,
+    /// The durability the ship was created with.
+    max_durability: Durability,
+    /// The durability of projectiles fired by the ship.
+    projectile_durability: Durability,
+    /// The pattern the ship's weapon fires projectiles in.
+    fire_pattern: FirePattern,
+    /// The multiplier of the recoil impulse firing applies to the ship, 1 conserves momentum and 0 disables recoil.
+    recoil: f32,
+    /// The speed of fired projectiles relative to the part of the ship's velocity they inherit.
+    muzzle_speed: f32,
+    /// The fraction of the ship's velocity fired projectiles inherit, 1 for all of it and 0 for none.
+    inheritance: f32,
+    /// The remaining simulated time after spawning during which the ship takes no collision damage.
+    spawn_protection: f32,
+    /// The amount of projectiles left to fire in the current burst.
+    burst_remaining: u8,
+    /// The time until the next projectile of the current burst is fired.
+    burst_timer: f32,
+    /// The ship's records of closest approach, top speed and skimming points.
+    record: FlightRecord,
+    /// The remaining fuel of the ship, between 0 and 1. Thrusting is only possible while fuel is left.
+    fuel: f32,
+    /// The mass the ship was created with, which the mass ejected as projectiles regenerates back to.
+    max_mass: f32,
 }
 
 impl SpaceObject {
     const ROT_ACCELERATION: f32 = 0.05;
     const LIN_ACCELARATION: f32 = 0.001;
+    /// Amount of fuel burnt per tick of thrusting.
+    const FUEL_BURN: f32 = 0.002;
+    /// Amount of fuel regenerated per tick.
+    const FUEL_REGEN: f32 = 0.0003;
+    /// The mass of a fired projectile, which is subtracted from the firing ship.
+    const PROJECTILE_MASS: f32 = 0.01;
+    /// Ejected mass regenerated by a ship per tick, one projectile per weapon cooldown so single shots can be fired nonstop.
+    const MASS_REGEN: f32 = 0.0001;
+    /// The default speed of fired projectiles relative to the part of the ship's velocity they inherit.
+    pub const DEFAULT_MUZZLE_SPEED: f32 = 0.8;
+    /// The collision layer celestial bodies are on by default.
+    pub const LAYER_BODIES: u32 = 1 << 0;
+    /// The collision layer ships are on by default.
+    pub const LAYER_SHIPS: u32 = 1 << 1;
+    /// The collision layer projectiles are on by default.
+    pub const LAYER_PROJECTILES: u32 = 1 << 2;
+    /// The mask of all collision layers.
+    pub const LAYER_ALL: u32 = u32::MAX;
+    /// The mass below which a ship can no longer fire, so its mass always stays positive.
+    const MIN_SHIP_MASS: f32 = 0.5;
+    /// The distance from a target body's surface within which a ship earns skimming points.
+    const SKIM_DISTANCE: f32 = 48.0;
+    /// The radius of an object's gravity well per square root of its mass.
+    const WELL_SCALE: f32 = 6.0;
+    /// The amount of rings a gravity well is drawn with.
+    const WELL_RINGS: usize = 12;
+    /// The ratio between size and radius used to derive sizes from densities.
+    const DENSITY_SIZE_SCALE: f32 = 2.0;
+    /// The simulated time an object flashes white for after being hit.
+    const FLASH_DURATION: f32 = 8.0;
+    /// The simulated time a protected ship takes to blink once.
+    const PROTECTION_BLINK_PERIOD: f32 = 16.0;
+    /// The amount of rings a glow is drawn with.
+    const GLOW_RINGS: usize = 8;
+    /// The durability of projectiles fired by ships.
+    const PROJECTILE_DURABILITY: Durability = Durability::Collisions(1);
     /// Creates a new space objects describing a ship
-    pub fn ship(position: Vec2, velocity: Vec2, ship_image: &Image, keymap: [KeyCode; 4]) -> Self {
+    pub fn ship(
+        position: Vec2,
+        velocity: Vec2,
+        sprite_key: SpriteKey,
+        ship_sprite: &SpriteTextures,
+        keymap: [KeyCode; 4],
+        durability: Durability,
+    ) -> Self {
         Self {
+            id: next_id(),
             position,
             velocity,
             angle: 0.0,
+            previous_position: position,
+            previous_angle: 0.0,
             mass: 1.0,
             size: 16.0,
-            sprite: Texture2D::from_image(ship_image),
+            sprite: ship_sprite.base.clone(),
+            variants: ship_sprite.variants.clone(),
+            sprite_key,
+            tint: WHITE,
             ship: Some(ShipInfo {
                 shot_cd: 0.0,
                 keymap,
+                extended_keymap: None,
+                assist_key: None,
+                max_durability: durability,
+                projectile_durability: Self::PROJECTILE_DURABILITY,
+                fire_pattern: FirePattern::Single,
+                recoil: 1.0,
+                muzzle_speed: Self::DEFAULT_MUZZLE_SPEED,
+                inheritance: 1.0,
+                spawn_protection: 0.0,
+                burst_remaining: 0,
+                burst_timer: 0.0,
+                record: FlightRecord {
+                    closest_approach: f32::INFINITY,
+                    top_speed: 0.0,
+                    skim_score: 0.0,
+                },
+                fuel: 1.0,
+                max_mass: 1.0,
             }),
-            collisions: Some(3),
+            durability,
+            owner: None,
+            charge: 0.0,
+            anchored: false,
+            hits: Vec::new(),
+            label: None,
+            path: None,
+            path_time: 0.0,
+            path_phase: 0.0,
+            glow: None,
+            flash_timer: 0.0,
+            collision_layer: Self::LAYER_SHIPS,
+            collision_mask: Self::LAYER_ALL,
+            gravity_group: 0,
+        }
+    }
+
+    /// Sets the key held to steer the ship into a circular orbit while the orbit assist is enabled.
+    pub fn with_assist_key(mut self, key: Option<KeyCode>) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.assist_key = key;
+        }
+        self
+    }
+
+    /// Enables reverse thrust and strafing for a ship, controlled by the keys for reverse, strafing left and strafing right.
+    pub fn with_extended_controls(mut self, keys: [KeyCode; 3]) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.extended_keymap = Some(keys);
+        }
+        self
+    }
+
+    /// Sets the pattern a ship's weapon fires projectiles in.
+    pub fn with_fire_pattern(mut self, fire_pattern: FirePattern) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.fire_pattern = fire_pattern;
+        }
+        self
+    }
+
+    /// Lets the projectiles of a ship pierce through up to `count` targets before expiring.
+    pub fn with_pierce(mut self, count: u8) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.projectile_durability = Durability::Collisions(count);
+        }
+        self
+    }
+
+    /// Sets the multiplier of the recoil a ship experiences when firing.
+    pub fn with_recoil(mut self, recoil: f32) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.recoil = recoil;
+        }
+        self
+    }
+
+    /// Sets the muzzle speed of a ship's projectiles and the fraction of the ship's velocity they inherit.
+    pub fn with_muzzle(mut self, speed: f32, inheritance: f32) -> Self {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.muzzle_speed = speed;
+            ship_info.inheritance = inheritance;
+        }
+        self
+    }
+
+    /// Sets the color the object's sprite is tinted with.
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Sets the electric charge of the object.
+    pub fn with_charge(mut self, charge: f32) -> Self {
+        self.charge = charge;
+        self
+    }
+
+    /// Sets the name displayed above the object.
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// Makes the object kinematic, following the given path instead of forces.
+    /// Circles are joined at the angle of the object's current position around their center, waypoint loops start at their first point.
+    pub fn with_path(mut self, path: Option<Path>) -> Self {
+        self.path_time = 0.0;
+        if let Some(path) = &path {
+            self.path_phase = match path {
+                Path::Circle { center, .. } => {
+                    let offset = self.position - *center;
+                    offset.y.atan2(offset.x)
+                }
+                Path::Waypoints { .. } => 0.,
+            };
+            (self.position, self.velocity) = path.state_at(0., self.path_phase);
+            self.previous_position = self.position;
         }
+        self.path = path;
+        self
+    }
+
+    /// Sets the pulsing halo drawn around the object.
+    pub fn with_glow(mut self, glow: Option<GlowParams>) -> Self {
+        self.glow = glow;
+        self
+    }
+
+    /// Moves the object to the given collision layers and lets it collide with the given mask of layers, keeping the current value of each that is `None`.
+    pub fn with_collision_filter(mut self, layer: Option<u32>, mask: Option<u32>) -> Self {
+        self.collision_layer = layer.unwrap_or(self.collision_layer);
+        self.collision_mask = mask.unwrap_or(self.collision_mask);
+        self
+    }
+
+    /// Moves the object to the given gravity group.
+    pub fn with_gravity_group(mut self, group: u8) -> Self {
+        self.gravity_group = group;
+        self
+    }
+
+    /// Sets wether the object is held in place.
+    pub fn with_anchored(mut self, anchored: bool) -> Self {
+        self.anchored = anchored;
+        self
     }
 
     /// Creates a new space object describing a celestial body, non-controllable and not a ship.
-    pub fn body(position: Vec2, velocity: Vec2, mass: f32, size: f32, image: &Image) -> Self {
+    pub fn body(
+        position: Vec2,
+        velocity: Vec2,
+        mass: f32,
+        size: f32,
+        sprite_key: SpriteKey,
+        textures: &SpriteTextures,
+        durability: Durability,
+    ) -> Self {
         Self {
+            id: next_id(),
             position,
             velocity,
             angle: 0.0,
+            previous_position: position,
+            previous_angle: 0.0,
             mass,
             size,
-            sprite: Texture2D::from_image(image),
+            sprite: textures.base.clone(),
+            variants: textures.variants.clone(),
+            sprite_key,
+            tint: WHITE,
             ship: None,
-            collisions: None,
+            durability,
+            owner: None,
+            charge: 0.0,
+            anchored: false,
+            hits: Vec::new(),
+            label: None,
+            path: None,
+            path_time: 0.0,
+            path_phase: 0.0,
+            glow: None,
+            flash_timer: 0.0,
+            collision_layer: Self::LAYER_BODIES,
+            collision_mask: Self::LAYER_ALL,
+            gravity_group: 0,
         }
     }
 
+    /// Creates an invisible, uncharged test object of the given mass at `position`, to sample the forces other objects exert there.
+    pub fn probe(position: Vec2, mass: f32) -> Self {
+        let textures = SpriteTextures {
+            base: Texture2D::empty(),
+            variants: Vec::new(),
+        };
+        Self::body(
+            position,
+            Vec2::ZERO,
+            mass,
+            0.,
+            SpriteKey::Projectile,
+            &textures,
+            Durability::Indestructible,
+        )
+    }
+
+    /// The size of an object of the given mass and density, which is its mass per squared radius.
+    pub fn size_from_density(mass: f32, density: f32) -> f32 {
+        Self::DENSITY_SIZE_SCALE * (mass / density).sqrt()
+    }
+
+    /// Resizes the object to match its mass at the given density, coupling size to mass instead of setting both independently.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.size = Self::size_from_density(self.mass, density);
+        self
+    }
+
     /// Returns wether this object is a ship or not.
     pub fn is_ship(&self) -> bool {
         self.ship.is_some()
     }
 
-    /// Reads from the input and controls the ship based on it.
-    pub fn interact(&mut self, images: &[Image]) -> Vec<SpaceObject> {
+    /// Returns wether this object is a celestial body, i.e. neither a ship nor a projectile.
+    pub fn is_body(&self) -> bool {
+        !self.is_ship() && !self.is_projectile()
+    }
+
+    /// A short description of what kind of object this is.
+    pub fn kind_name(&self) -> &'static str {
+        if self.is_ship() {
+            "ship"
+        } else if self.is_projectile() {
+            "projectile"
+        } else {
+            "body"
+        }
+    }
+
+    /// Checks the state of this object for non-finite values and describes the first one found.
+    pub fn check_finite(&self) -> Result<(), String> {
+        if !self.position.is_finite() {
+            Err(format!("non-finite position {}", self.position))
+        } else if !self.velocity.is_finite() {
+            Err(format!("non-finite velocity {}", self.velocity))
+        } else if !self.angle.is_finite() {
+            Err(format!("non-finite angle {}", self.angle))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns wether this object is a projectile or not.
+    pub fn is_projectile(&self) -> bool {
+        self.owner.is_some()
+    }
+
+    /// Reads from the input and controls the ship based on it, advancing its state by the time step `dt`.
+    pub fn interact(
+        &mut self,
+        sprites: &HashMap<SpriteKey, SpriteTextures>,
+        dt: f32,
+    ) -> Vec<SpaceObject> {
         let mut spawns = Vec::new();
 
         // If not a ship, nothing to do here.
@@ -78,65 +596,446 @@ impl SpaceObject {
             return spawns;
         }
 
+        let thrusters = self.thrusters();
+        let powered = self.burn(&thrusters, dt);
         // unwrap info (must be there because of filter)
         let ship_info = self.ship.as_mut().unwrap();
-        // Acceleration
-        if is_key_down(ship_info.keymap[0]) {
-            self.velocity += Vec2::new(self.angle.cos(), self.angle.sin()) * Self::LIN_ACCELARATION;
-            self.sprite = Texture2D::from_image(&images[1]);
+        // Show the cached sprite of the thrust state, only touching the variants when it changes
+        let sprite = &sprites[&if powered {
+            SpriteKey::ShipPowered
         } else {
-            self.sprite = Texture2D::from_image(&images[0]);
+            SpriteKey::Ship
+        }];
+        if self.sprite != sprite.base {
+            self.sprite = sprite.base.clone();
+            self.variants = sprite.variants.clone();
         }
         // Turning
         if is_key_down(ship_info.keymap[1]) {
-            self.angle += Self::ROT_ACCELERATION;
+            self.angle += Self::ROT_ACCELERATION * dt;
         }
         if is_key_down(ship_info.keymap[2]) {
-            self.angle -= Self::ROT_ACCELERATION;
-        }
-        // Weapons
-        if is_key_down(ship_info.keymap[3]) {
-            if ship_info.shot_cd <= 0.0 {
-                spawns.push(SpaceObject {
-                    position: self.position
-                        + Vec2::new(self.angle.cos(), self.angle.sin()) * self.size / 1.5,
-                    velocity: self.velocity + Vec2::new(self.angle.cos(), self.angle.sin()) * 0.8,
-                    angle: self.angle,
-                    mass: 0.01,
-                    size: 4.0,
-                    sprite: Texture2D::from_image(&images[2]),
-                    ship: None,
-                    collisions: Some(1),
-                });
-                ship_info.shot_cd = 1.0;
+            self.angle -= Self::ROT_ACCELERATION * dt;
+        }
+        // Weapons, collecting the angles relative to the facing at which projectiles leave this tick
+        let mut shots = Vec::new();
+        if is_key_down(ship_info.keymap[3])
+            && ship_info.shot_cd <= 0.0
+            && self.mass - Self::PROJECTILE_MASS >= Self::MIN_SHIP_MASS
+        {
+            match ship_info.fire_pattern {
+                FirePattern::Single => shots.push(0.0),
+                FirePattern::Spread { count, arc } => {
+                    // Fan the projectiles out evenly across the arc, centered on the facing
+                    for i in 0..count {
+                        shots.push(if count > 1 {
+                            arc * (i as f32 / (count - 1) as f32 - 0.5)
+                        } else {
+                            0.0
+                        });
+                    }
+                }
+                FirePattern::Burst { count, interval } => {
+                    shots.push(0.0);
+                    ship_info.burst_remaining = count.saturating_sub(1);
+                    ship_info.burst_timer = interval;
+                }
+            }
+            ship_info.shot_cd = 1.0;
+        }
+        // Remaining projectiles of a burst
+        if let FirePattern::Burst { interval, .. } = ship_info.fire_pattern {
+            if ship_info.burst_remaining > 0 {
+                ship_info.burst_timer -= dt;
+                if ship_info.burst_timer <= 0.0 {
+                    shots.push(0.0);
+                    ship_info.burst_remaining -= 1;
+                    ship_info.burst_timer += interval;
+                }
             }
         }
         // Weapon cooldown
-        ship_info.shot_cd = (ship_info.shot_cd - 0.01).max(0.0);
+        ship_info.shot_cd = (ship_info.shot_cd - 0.01 * dt).max(0.0);
+
+        for angle in shots {
+            spawns.extend(self.fire(angle, sprites));
+        }
         spawns
     }
 
-    /// Draws the object to its position on the screen.
-    pub fn draw(&self) {
-        self.sprite.set_filter(FilterMode::Nearest);
+    /// The directions of all thrusters of a ship whose keys are currently held: along the facing, and optionally against and perpendicular to it.
+    pub fn thrusters(&self) -> Vec<Vec2> {
+        let mut thrusters = Vec::new();
+        let Some(ship_info) = &self.ship else {
+            return thrusters;
+        };
+
+        let facing = Vec2::new(self.angle.cos(), self.angle.sin());
+        if is_key_down(ship_info.keymap[0]) {
+            thrusters.push(facing);
+        }
+        if let Some([reverse, left, right]) = ship_info.extended_keymap {
+            if is_key_down(reverse) {
+                thrusters.push(-facing);
+            }
+            if is_key_down(left) {
+                thrusters.push(facing.perp());
+            }
+            if is_key_down(right) {
+                thrusters.push(-facing.perp());
+            }
+        }
+        thrusters
+    }
+
+    /// Fires the thrusters in the given directions over the time step `dt`, then regenerates fuel.
+    /// Thrusting is only possible while fuel is left, every active thruster burns fuel. Returns wether the ship thrusted.
+    pub fn burn(&mut self, thrusters: &[Vec2], dt: f32) -> bool {
+        let Some(ship_info) = &mut self.ship else {
+            return false;
+        };
+        let powered = !thrusters.is_empty() && ship_info.fuel > 0.0;
+        if powered {
+            for direction in thrusters {
+                self.velocity += *direction * Self::LIN_ACCELARATION * dt;
+                ship_info.fuel = (ship_info.fuel - Self::FUEL_BURN * dt).max(0.0);
+            }
+        }
+        ship_info.fuel = (ship_info.fuel + Self::FUEL_REGEN * dt).min(1.0);
+        powered
+    }
+
+    /// Changes the velocity of the object by `delta`, unless it is anchored or kinematic.
+    pub fn accelerate(&mut self, delta: Vec2) {
+        if !self.anchored && self.path.is_none() {
+            self.velocity += delta;
+        }
+    }
+
+    /// Fires a projectile at the given angle relative to the ship's facing, inheriting the configured fraction of the ship's velocity.
+    /// The ejected projectile mass is lost by the ship, so nothing is fired if the ship would become too light.
+    fn fire(
+        &mut self,
+        angle_offset: f32,
+        sprites: &HashMap<SpriteKey, SpriteTextures>,
+    ) -> Option<SpaceObject> {
+        let ship_info = self.ship.as_ref()?;
+        if self.mass - Self::PROJECTILE_MASS < Self::MIN_SHIP_MASS {
+            return None;
+        }
+
+        let angle = self.angle + angle_offset;
+        let position =
+            self.position + Vec2::new(self.angle.cos(), self.angle.sin()) * self.size / 1.5;
+        let muzzle_velocity = Vec2::new(angle.cos(), angle.sin()) * ship_info.muzzle_speed;
+        let projectile = SpaceObject {
+            id: next_id(),
+            position,
+            velocity: self.velocity * ship_info.inheritance + muzzle_velocity,
+            angle,
+            previous_position: position,
+            previous_angle: angle,
+            mass: Self::PROJECTILE_MASS,
+            size: 4.0,
+            sprite: sprites[&SpriteKey::Projectile].base.clone(),
+            variants: sprites[&SpriteKey::Projectile].variants.clone(),
+            sprite_key: SpriteKey::Projectile,
+            // Projectiles show whose shots they are
+            tint: self.tint,
+            ship: None,
+            durability: ship_info.projectile_durability,
+            owner: Some(self.id),
+            charge: 0.0,
+            anchored: false,
+            hits: Vec::new(),
+            label: None,
+            path: None,
+            path_time: 0.0,
+            path_phase: 0.0,
+            glow: None,
+            flash_timer: 0.0,
+            collision_layer: Self::LAYER_PROJECTILES,
+            // Projectiles pass through each other
+            collision_mask: Self::LAYER_ALL & !Self::LAYER_PROJECTILES,
+            // Shots fall the way their ship does
+            gravity_group: self.gravity_group,
+        };
+        self.mass -= Self::PROJECTILE_MASS;
+        // The ship takes the opposite of the projectile's muzzle momentum, scaled by the recoil multiplier.
+        // Velocity not inherited by the projectile causes no recoil, so it does not depend on how fast the ship moves.
+        self.velocity -= muzzle_velocity * Self::PROJECTILE_MASS * ship_info.recoil / self.mass;
+        Some(projectile)
+    }
+
+    /// Draws the object to its position on the screen, interpolated by `alpha` between the previous and current physics tick.
+    /// Glowing objects pulse with the elapsed simulated `time`, and sprites with variants pick the one fitting `pixels_per_unit` of the current camera.
+    pub fn draw(&self, alpha: f32, time: f32, pixels_per_unit: f32) {
+        let position = self.get_interpolated_position(alpha);
+        if let Some(glow) = &self.glow {
+            let pulse = 1. + glow.pulse * (std::f32::consts::TAU * time / glow.period).sin();
+            let radius = self.size / 2. * (1. + (glow.radius - 1.) * pulse);
+            // Stacked translucent circles grow more opaque towards the object
+            for ring in 0..Self::GLOW_RINGS {
+                let fraction = 1. - ring as f32 / Self::GLOW_RINGS as f32;
+                draw_circle(
+                    position.x,
+                    position.y,
+                    self.size / 2. + (radius - self.size / 2.) * fraction,
+                    Color {
+                        a: glow.color.a * pulse / Self::GLOW_RINGS as f32,
+                        ..glow.color
+                    },
+                );
+            }
+        }
+        // Draw the resolution closest to the sprite's size on screen, scaled to the size of the base sprite
+        let size = vec2(self.sprite.width(), self.sprite.height());
+        let apparent_width = size.x * pixels_per_unit;
+        let texture = std::iter::once(&self.sprite)
+            .chain(self.variants.iter())
+            .filter(|texture| texture.width() >= apparent_width)
+            .min_by(|a, b| a.width().total_cmp(&b.width()))
+            .or_else(|| {
+                self.variants
+                    .last()
+                    .filter(|variant| variant.width() > size.x)
+            })
+            .unwrap_or(&self.sprite);
+        // Blend the tint towards white while flashing after a hit
+        let flash = self.flash_timer / Self::FLASH_DURATION;
+        // Protected ships blink between translucent and opaque
+        let opacity = match &self.ship {
+            Some(ship_info)
+                if ship_info.spawn_protection > 0.
+                    && ship_info.spawn_protection % Self::PROTECTION_BLINK_PERIOD
+                        < Self::PROTECTION_BLINK_PERIOD / 2. =>
+            {
+                0.3
+            }
+            _ => 1.,
+        };
+        let tint = Color::new(
+            self.tint.r + (1. - self.tint.r) * flash,
+            self.tint.g + (1. - self.tint.g) * flash,
+            self.tint.b + (1. - self.tint.b) * flash,
+            self.tint.a * opacity,
+        );
         draw_texture_ex(
-            &self.sprite,
-            self.position.x - self.size / 2.,
-            self.position.y - self.size / 2.,
-            WHITE,
+            texture,
+            position.x - self.size / 2.,
+            position.y - self.size / 2.,
+            tint,
             DrawTextureParams {
-                rotation: self.angle,
+                dest_size: Some(size),
+                rotation: self.previous_angle + (self.angle - self.previous_angle) * alpha,
                 ..Default::default()
             },
         );
     }
 
-    /// Moves the ship by its velocity. If a force is passed, it is first accelerated accordingly.
-    pub fn perform_movement(&mut self, force: impl Into<Option<Vec2>>) {
+    /// Draws a gravity well around the object as concentric translucent rings, sized by the square root of its mass.
+    pub fn draw_well(&self, alpha: f32) {
+        let position = self.get_interpolated_position(alpha);
+        let radius = self.mass.sqrt() * Self::WELL_SCALE;
+        // Objects with little mass have no visible well outside their own sprite
+        if radius <= self.size / 2. {
+            return;
+        }
+        for ring in 0..Self::WELL_RINGS {
+            let fraction = 1. - ring as f32 / Self::WELL_RINGS as f32;
+            draw_circle(
+                position.x,
+                position.y,
+                self.size / 2. + (radius - self.size / 2.) * fraction,
+                Color::new(0.2, 0.3, 0.6, 0.05),
+            );
+        }
+    }
+
+    /// Returns the current physical and gameplay state of this object.
+    pub fn state(&self) -> ObjectState {
+        ObjectState {
+            id: self.id,
+            position: self.position,
+            velocity: self.velocity,
+            angle: self.angle,
+            mass: self.mass,
+            durability: self.durability,
+            path_time: self.path_time,
+            flash_timer: self.flash_timer,
+            hits: self.hits.clone(),
+            ship: self.ship.clone(),
+        }
+    }
+
+    /// Resets this object to a previously stored state, without interpolating towards it.
+    pub fn restore(&mut self, state: &ObjectState) {
+        self.position = state.position;
+        self.velocity = state.velocity;
+        self.angle = state.angle;
+        self.mass = state.mass;
+        self.durability = state.durability;
+        self.path_time = state.path_time;
+        self.flash_timer = state.flash_timer;
+        self.hits.clone_from(&state.hits);
+        self.ship.clone_from(&state.ship);
+        self.store_previous();
+    }
+
+    /// Remembers the current position and angle as the state at the start of a new physics tick.
+    pub fn store_previous(&mut self) {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
+    }
+
+    /// Moves the ship by its velocity over the time step `dt`. If a force is passed, it is first accelerated accordingly.
+    /// Objects without positive mass cannot be accelerated by forces, anchored objects do not move at all.
+    /// Afterwards, speeds above `max_speed` are scaled down to it, keeping the direction of movement.
+    pub fn perform_movement(&mut self, force: impl Into<Option<Vec2>>, dt: f32, max_speed: f32) {
+        if self.anchored {
+            return;
+        }
+        // Kinematic objects ignore forces and follow their path
+        if let Some(path) = &self.path {
+            self.path_time += dt;
+            (self.position, self.velocity) = path.state_at(self.path_time, self.path_phase);
+            return;
+        }
         if let Some(f) = force.into() {
-            self.velocity += f / self.mass;
+            if self.mass > 0.0 {
+                self.velocity += f / self.mass * dt;
+            }
+        }
+        self.velocity = self.velocity.clamp_length_max(max_speed);
+        self.position += self.velocity * dt;
+    }
+
+    /// Moves the object into the square arena of the given half side length, as if opposite sides of the arena were connected.
+    pub fn wrap(&mut self, half_size: f32) {
+        if self.anchored || self.path.is_some() {
+            return;
+        }
+        let unwrapped = self.position;
+        self.position.x = (self.position.x + half_size).rem_euclid(2. * half_size) - half_size;
+        self.position.y = (self.position.y + half_size).rem_euclid(2. * half_size) - half_size;
+        // Move the previous position along, so drawing does not interpolate across the whole arena
+        self.previous_position += self.position - unwrapped;
+    }
+
+    /// The color the object's sprite is tinted with.
+    pub fn get_tint(&self) -> Color {
+        self.tint
+    }
+
+    /// The electric charge of the object.
+    pub fn get_charge(&self) -> f32 {
+        self.charge
+    }
+
+    /// The name displayed above the object, if any.
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// The pulsing halo drawn around the object, if it glows.
+    pub fn get_glow(&self) -> Option<GlowParams> {
+        self.glow
+    }
+
+    /// The path followed by the object if it is kinematic.
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
+    /// Wether the object is held in place.
+    pub fn is_anchored(&self) -> bool {
+        self.anchored
+    }
+
+    /// Wether the object can be moved by anything but its path, being neither anchored nor kinematic.
+    pub fn is_movable(&self) -> bool {
+        !self.anchored && self.path.is_none()
+    }
+
+    /// Moves the object by `offset` without it having traveled there, unless it is not movable.
+    pub fn nudge(&mut self, offset: Vec2) {
+        if self.is_movable() {
+            self.position += offset;
+            self.previous_position += offset;
         }
-        self.position += self.velocity;
+    }
+
+    /// The reciprocal of the object's mass, or zero for anchored and kinematic objects that behave as if infinitely heavy.
+    fn inverse_mass(&self) -> f32 {
+        if self.anchored || self.path.is_some() {
+            0.
+        } else {
+            1. / self.mass
+        }
+    }
+
+    /// The sprite the object was created with.
+    pub fn get_sprite_key(&self) -> SpriteKey {
+        self.sprite_key
+    }
+
+    /// The amount of targets the projectiles fired by this object pierce, if it is a ship.
+    pub fn get_pierce(&self) -> Option<u8> {
+        match self.ship.as_ref()?.projectile_durability {
+            Durability::Collisions(count) => Some(count),
+            Durability::Indestructible => None,
+        }
+    }
+
+    /// The multiplier of the recoil experienced when firing, if this object is a ship.
+    pub fn get_recoil(&self) -> Option<f32> {
+        self.ship.as_ref().map(|ship_info| ship_info.recoil)
+    }
+
+    /// The muzzle speed of projectiles and the fraction of velocity they inherit, if this object is a ship.
+    pub fn get_muzzle(&self) -> Option<(f32, f32)> {
+        self.ship
+            .as_ref()
+            .map(|ship_info| (ship_info.muzzle_speed, ship_info.inheritance))
+    }
+
+    /// The collision layers of this object and the mask of layers it can collide with.
+    pub fn get_collision_filter(&self) -> (u32, u32) {
+        (self.collision_layer, self.collision_mask)
+    }
+
+    /// The gravity group of this object.
+    pub fn get_gravity_group(&self) -> u8 {
+        self.gravity_group
+    }
+
+    /// The stable id of this object.
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// The pattern the weapon fires in if this object is a ship.
+    pub fn get_fire_pattern(&self) -> Option<FirePattern> {
+        self.ship.as_ref().map(|ship_info| ship_info.fire_pattern)
+    }
+
+    /// The key held to steer this ship into a circular orbit, if it is a ship with one.
+    pub fn get_assist_key(&self) -> Option<KeyCode> {
+        self.ship
+            .as_ref()
+            .and_then(|ship_info| ship_info.assist_key)
+    }
+
+    /// The keymap and optional extended keymap if this object is a ship.
+    pub fn get_keymaps(&self) -> Option<([KeyCode; 4], Option<[KeyCode; 3]>)> {
+        self.ship
+            .as_ref()
+            .map(|ship_info| (ship_info.keymap, ship_info.extended_keymap))
+    }
+
+    /// The amount of collisions this object can still survive.
+    pub fn get_durability(&self) -> Durability {
+        self.durability
     }
 
     /// The objects position vector as a point.
@@ -144,35 +1043,234 @@ impl SpaceObject {
         self.position
     }
 
+    /// The objects position, interpolated by `alpha` between the previous and current physics tick.
+    pub fn get_interpolated_position(&self, alpha: f32) -> Vec2 {
+        self.previous_position.lerp(self.position, alpha)
+    }
+
     /// The objects velocity vector.
-    #[allow(dead_code)]
     pub fn get_velocity(&self) -> Vec2 {
         self.velocity
     }
 
+    /// The objects size.
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+
     /// The objects mass.
     pub fn get_mass(&self) -> f32 {
         self.mass
     }
 
+    /// Updates the flight record of this ship with its current speed and its approach to the target body, given by position and size, over the time step `dt`.
+    pub fn record_flight(&mut self, target: Option<(Vec2, f32)>, dt: f32) {
+        let speed = self.velocity.length();
+        let distance = target
+            .map(|(position, size)| (position - self.position).length() - (size + self.size) / 2.);
+        if let Some(ship_info) = &mut self.ship {
+            let record = &mut ship_info.record;
+            record.top_speed = record.top_speed.max(speed);
+            if let Some(distance) = distance {
+                record.closest_approach = record.closest_approach.min(distance.max(0.));
+                // Skimming closer to the surface is riskier and earns more points
+                if distance > 0. && distance < Self::SKIM_DISTANCE {
+                    record.skim_score += (1. - distance / Self::SKIM_DISTANCE) * dt;
+                }
+            }
+        }
+    }
+
+    /// The flight record if this object is a ship.
+    pub fn get_record(&self) -> Option<FlightRecord> {
+        self.ship.as_ref().map(|ship_info| ship_info.record)
+    }
+
+    /// The fraction of remaining fuel if this object is a ship.
+    pub fn get_fuel(&self) -> Option<f32> {
+        self.ship.as_ref().map(|ship_info| ship_info.fuel)
+    }
+
+    /// The fraction of the mass that can still be fired as projectiles if this object is a ship.
+    pub fn get_ammo(&self) -> Option<f32> {
+        self.ship.as_ref().map(|ship_info| {
+            ((self.mass - Self::MIN_SHIP_MASS) / (ship_info.max_mass - Self::MIN_SHIP_MASS))
+                .clamp(0.0, 1.0)
+        })
+    }
+
+    /// The fraction of remaining collisions if this object is a ship.
+    pub fn get_health(&self) -> Option<f32> {
+        self.ship.as_ref().map(
+            |ship_info| match (self.durability, ship_info.max_durability) {
+                (Durability::Collisions(left), Durability::Collisions(max)) if max > 0 => {
+                    left as f32 / max as f32
+                }
+                _ => 1.0,
+            },
+        )
+    }
+
+    /// Checks wether the rules allow this object to collide with the other object at all.
+    pub fn can_collide(&self, other: &SpaceObject, rules: &CollisionRules) -> bool {
+        if rules.disabled {
+            return false;
+        }
+        // Both objects need to be on a layer the other can collide with
+        if self.collision_layer & other.collision_mask == 0
+            || other.collision_layer & self.collision_mask == 0
+        {
+            return false;
+        }
+        // Destroyed objects cannot hit anything else, and piercing projectiles hit every target only once
+        if !self.collisions_left()
+            || !other.collisions_left()
+            || self.hits.contains(&other.id)
+            || other.hits.contains(&self.id)
+        {
+            return false;
+        }
+        // Without friendly fire, projectiles pass through the ship that fired them
+        rules.friendly_fire || (self.owner != Some(other.id) && other.owner != Some(self.id))
+    }
+
+    /// The kinetic energy of this object and the other object relative to their common center of mass, released if they collided inelastically.
+    /// Massless pairs release no energy.
+    pub fn impact_energy(&self, other: &SpaceObject) -> f32 {
+        let total_mass = self.mass + other.mass;
+        if total_mass <= 0. {
+            return 0.;
+        }
+        let reduced_mass = self.mass * other.mass / total_mass;
+        0.5 * reduced_mass * (self.velocity - other.velocity).length_squared()
+    }
+
+    /// Checks wether this object overlaps the other object.
+    pub fn overlaps(&self, other: &SpaceObject) -> bool {
+        (self.position - other.position).length() * 2. < self.size + other.size
+    }
+
     /// Checks if this object collides with the other object, and if yes, registers a collision on both objects, reducing their allowed collisions by 1 if present.
-    pub fn collide(&mut self, other: &mut SpaceObject) {
-        if (self.position - other.position).length() * 2. < self.size + other.size {
-            if let Some(c) = &mut self.collisions {
-                *c -= 1;
+    /// Depending on the rules, projectiles may instead ricochet off celestial bodies and ships bounce off each other.
+    /// Returns the point of contact if the collision damaged the objects.
+    pub fn collide(&mut self, other: &mut SpaceObject, rules: &CollisionRules) -> Option<Vec2> {
+        // The point between both centers where their surfaces touch
+        let contact =
+            self.position + (other.position - self.position) * self.size / (self.size + other.size);
+        if self.can_collide(other, rules) && self.overlaps(other) {
+            if rules.bounce_off_bodies {
+                if self.is_projectile() && other.is_body() {
+                    self.ricochet(other, rules.restitution);
+                    return None;
+                }
+                if other.is_projectile() && self.is_body() {
+                    other.ricochet(self, rules.restitution);
+                    return None;
+                }
+            }
+            if rules.ship_bounce && self.is_ship() && other.is_ship() {
+                if self.bounce(other) {
+                    self.damage(rules.ship_ram_damage, rules);
+                    other.damage(rules.ship_ram_damage, rules);
+                    return Some(contact);
+                }
+                return None;
             }
-            if let Some(c) = &mut other.collisions {
-                *c -= 1;
+            self.damage(1, rules);
+            other.damage(1, rules);
+            if self.is_projectile() {
+                self.hits.push(other.id);
             }
+            if other.is_projectile() {
+                other.hits.push(self.id);
+            }
+            return Some(contact);
         }
+        None
     }
 
-    /// Returns wether this element can still survive collisions, i.e.
+    /// Reduces the allowed collisions of this object by `amount` unless it is indestructible or a protected ship.
+    /// Damaged objects flash white for a moment.
+    fn damage(&mut self, amount: u8, rules: &CollisionRules) {
+        if self.is_protected() || (rules.invincible_ships && self.is_ship()) {
+            return;
+        }
+        if let Durability::Collisions(c) = &mut self.durability {
+            *c = c.saturating_sub(amount);
+        }
+        self.flash_timer = Self::FLASH_DURATION;
+    }
+
+    /// Lets the white flash after being hit and the spawn protection of ships run out over the time step `dt`.
+    /// Ships also regain the mass they ejected as projectiles, so they are never disarmed for good.
+    pub fn update_timers(&mut self, dt: f32) {
+        self.flash_timer = (self.flash_timer - dt).max(0.0);
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.spawn_protection = (ship_info.spawn_protection - dt).max(0.0);
+            if self.mass < ship_info.max_mass {
+                self.mass = (self.mass + Self::MASS_REGEN * dt).min(ship_info.max_mass);
+            }
+        }
+    }
+
+    /// Protects a ship from collision damage for the simulated time `duration`. Has no effect on other objects.
+    pub fn protect(&mut self, duration: f32) {
+        if let Some(ship_info) = &mut self.ship {
+            ship_info.spawn_protection = duration;
+        }
+    }
+
+    /// Wether this object is a ship still protected from collision damage after spawning.
+    pub fn is_protected(&self) -> bool {
+        self.ship
+            .as_ref()
+            .is_some_and(|ship_info| ship_info.spawn_protection > 0.)
+    }
+
+    /// Performs an elastic collision between this object and the other, exchanging momentum along their normal and separating them.
+    /// Returns wether the objects were approaching each other.
+    fn bounce(&mut self, other: &mut SpaceObject) -> bool {
+        let delta = other.position - self.position;
+        let normal = delta.try_normalize().unwrap_or(Vec2::X);
+
+        let (self_inverse, other_inverse) = (self.inverse_mass(), other.inverse_mass());
+        let total_inverse = self_inverse + other_inverse;
+        if total_inverse == 0. {
+            return false;
+        }
+
+        // Push both objects apart so they no longer overlap, anchored objects are not moved
+        let overlap = (self.size + other.size) / 2. - delta.length();
+        self.position -= normal * overlap * self_inverse / total_inverse;
+        other.position += normal * overlap * other_inverse / total_inverse;
+
+        let approach = (self.velocity - other.velocity).dot(normal);
+        if approach <= 0. {
+            return false;
+        }
+        let impulse = 2. * approach / total_inverse;
+        self.velocity -= normal * impulse * self_inverse;
+        other.velocity += normal * impulse * other_inverse;
+        true
+    }
+
+    /// Reflects the velocity of this object about the surface normal of the body, keeping the `restitution` fraction of its speed, and places it just outside the body's surface.
+    fn ricochet(&mut self, body: &SpaceObject, restitution: f32) {
+        let normal = (self.position - body.position)
+            .try_normalize()
+            .unwrap_or(-self.velocity.normalize_or_zero());
+        // Only reflect if moving into the body, so a bounce cannot be undone in the same frame
+        if self.velocity.dot(normal) < 0. {
+            self.velocity = (self.velocity - 2. * self.velocity.dot(normal) * normal) * restitution;
+        }
+        self.position = body.position + normal * (self.size + body.size) / 2. * 1.01;
+    }
+
+    /// Returns wether this element can still survive collisions, i.e. is indestructible or has collisions left.
     pub fn collisions_left(&self) -> bool {
-        if let Some(c) = self.collisions {
-            c > 0
-        } else {
-            true
+        match self.durability {
+            Durability::Indestructible => true,
+            Durability::Collisions(c) => c > 0,
         }
     }
 }
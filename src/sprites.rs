@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+/// Names a sprite used by the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpriteKey {
+    /// A ship coasting.
+    Ship,
+    /// A ship firing its thrusters.
+    ShipPowered,
+    /// A projectile fired by a ship.
+    Projectile,
+    /// A star.
+    Sun,
+    /// A planet.
+    Earth,
+}
+
+impl SpriteKey {
+    /// All sprites, in the order they are loaded.
+    pub const ALL: [SpriteKey; 5] = [
+        SpriteKey::Ship,
+        SpriteKey::ShipPowered,
+        SpriteKey::Projectile,
+        SpriteKey::Sun,
+        SpriteKey::Earth,
+    ];
+
+    /// The name of the sprite, used for its file and in scenarios.
+    pub fn name(self) -> &'static str {
+        match self {
+            SpriteKey::Ship => "ship",
+            SpriteKey::ShipPowered => "ship_power",
+            SpriteKey::Projectile => "projectile",
+            SpriteKey::Sun => "sun",
+            SpriteKey::Earth => "earth",
+        }
+    }
+
+    /// Finds the sprite with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.name() == name)
+    }
+
+    /// The baked-in image used if the sprite's file cannot be loaded.
+    fn fallback(self) -> &'static [u8] {
+        match self {
+            SpriteKey::Ship => include_bytes!("../assets/ship.png"),
+            SpriteKey::ShipPowered => include_bytes!("../assets/ship_power.png"),
+            SpriteKey::Projectile => include_bytes!("../assets/projectile.png"),
+            SpriteKey::Sun => include_bytes!("../assets/sun.png"),
+            SpriteKey::Earth => include_bytes!("../assets/earth.png"),
+        }
+    }
+}
+
+/// The textures of a sprite: the one setting its size and optional variants of other resolutions.
+/// They are uploaded to the GPU once when loading and shared by every object drawn with them.
+#[derive(Debug, Clone)]
+pub struct SpriteTextures {
+    /// The texture loaded from `<name>.png` or baked in, whose size the sprite is drawn at.
+    pub base: Texture2D,
+    /// The textures loaded from `<name>@<width>.png`, sorted by width.
+    pub variants: Vec<Texture2D>,
+}
+
+/// Loads all sprites from PNG files `<name>.png` in the assets directory, with their variants `<name>@<width>.png`, for example `ship@64.png`.
+/// Sprites that cannot be loaded are reported and replaced by their baked-in versions, variants that cannot be loaded are reported and skipped.
+pub fn load_sprites(
+    assets_dir: &str,
+) -> Result<HashMap<SpriteKey, SpriteTextures>, macroquad::Error> {
+    let mut sprites = HashMap::new();
+    // Variants are optional, so a missing directory was already reported with the base sprites
+    let files = std::fs::read_dir(assets_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for key in SpriteKey::ALL {
+        let name = key.name();
+        let path = std::path::Path::new(assets_dir).join(format!("{name}.png"));
+        let image = match read_png(&path) {
+            Ok(image) => image,
+            Err(err) => {
+                eprintln!(
+                    "Could not load sprite `{name}` from {}: {err}. Using built-in sprite.",
+                    path.display()
+                );
+                Image::from_file_with_format(key.fallback(), Some(ImageFormat::Png))?
+            }
+        };
+
+        let prefix = format!("{name}@");
+        let mut variants = files
+            .iter()
+            .filter(|path| {
+                path.extension().is_some_and(|extension| extension == "png")
+                    && path.file_stem().is_some_and(|stem| {
+                        stem.to_string_lossy()
+                            .strip_prefix(&prefix)
+                            .is_some_and(|width| width.parse::<u32>().is_ok())
+                    })
+            })
+            .filter_map(|path| match read_png(path) {
+                Ok(image) => Some(image),
+                Err(err) => {
+                    eprintln!("Could not load sprite variant {}: {err}", path.display());
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        variants.sort_by_key(|variant| variant.width);
+
+        sprites.insert(
+            key,
+            SpriteTextures {
+                base: pixelated_texture(&image),
+                variants: variants.iter().map(pixelated_texture).collect(),
+            },
+        );
+    }
+
+    Ok(sprites)
+}
+
+/// Uploads the image to the GPU as a texture drawn with crisp pixels instead of blurred when scaled.
+fn pixelated_texture(image: &Image) -> Texture2D {
+    let texture = Texture2D::from_image(image);
+    texture.set_filter(FilterMode::Nearest);
+    texture
+}
+
+/// Reads the PNG image at `path`.
+fn read_png(path: &std::path::Path) -> Result<Image, String> {
+    let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+    Image::from_file_with_format(&bytes, Some(ImageFormat::Png)).map_err(|err| err.to_string())
+}
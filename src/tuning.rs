@@ -0,0 +1,212 @@
+use macroquad::prelude::*;
+
+/// A simulation parameter that can be adjusted at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    /// The gravitational constant.
+    Gravity,
+    /// The power of the distance gravity falls off with.
+    GravityExponent,
+    /// The constant of the force between charged objects.
+    Coulomb,
+    /// The factor simulated time passes at outside of slow motion.
+    Speed,
+    /// The amount of substeps every physics step is divided into.
+    Substeps,
+    /// The time after a collision before the same pair of objects can collide again.
+    CollisionCooldown,
+    /// The amount of collisions a ship loses when ramming another ship.
+    RamDamage,
+    /// The fraction of their speed projectiles keep when ricocheting off bodies.
+    Restitution,
+    /// The amount of objects above which the oldest projectiles are removed.
+    MaxObjects,
+    /// The amount of heatmap cells along each axis.
+    FieldResolution,
+    /// Wether objects pass through each other without colliding.
+    NoCollisions,
+    /// Wether gravity is switched off.
+    NoGravity,
+    /// Wether all objects except ships and projectiles are held in place.
+    FreezeBodies,
+    /// Wether ships take no collision damage.
+    InvincibleShips,
+}
+
+/// How a parameter changes per adjustment.
+enum Step {
+    /// The value is multiplied or divided by the factor.
+    Scale(f32),
+    /// The value is increased or decreased by the amount.
+    Add(f32),
+    /// The value is switched between off (0) and on (1).
+    Toggle,
+}
+
+impl Parameter {
+    /// All parameters, in the order they are listed.
+    pub const ALL: [Parameter; 14] = [
+        Parameter::Gravity,
+        Parameter::GravityExponent,
+        Parameter::Coulomb,
+        Parameter::Speed,
+        Parameter::Substeps,
+        Parameter::CollisionCooldown,
+        Parameter::RamDamage,
+        Parameter::Restitution,
+        Parameter::MaxObjects,
+        Parameter::FieldResolution,
+        Parameter::NoCollisions,
+        Parameter::NoGravity,
+        Parameter::FreezeBodies,
+        Parameter::InvincibleShips,
+    ];
+
+    /// The index of the first sandbox toggle, which are listed under their own heading.
+    const FIRST_TOGGLE: usize = 10;
+
+    /// The name the parameter is listed with.
+    fn name(self) -> &'static str {
+        match self {
+            Parameter::Gravity => "Gravity",
+            Parameter::GravityExponent => "Gravity falloff",
+            Parameter::Coulomb => "Coulomb constant",
+            Parameter::Speed => "Simulation speed",
+            Parameter::Substeps => "Substeps",
+            Parameter::CollisionCooldown => "Collision cooldown",
+            Parameter::RamDamage => "Ram damage",
+            Parameter::Restitution => "Ricochet restitution",
+            Parameter::MaxObjects => "Max objects",
+            Parameter::FieldResolution => "Heatmap resolution",
+            Parameter::NoCollisions => "No collisions",
+            Parameter::NoGravity => "No gravity",
+            Parameter::FreezeBodies => "Freeze bodies",
+            Parameter::InvincibleShips => "Invincible ships",
+        }
+    }
+
+    /// The smallest and largest allowed value of the parameter.
+    fn range(self) -> (f32, f32) {
+        match self {
+            Parameter::Gravity => (0.001, 10.),
+            Parameter::GravityExponent => (0.5, 4.),
+            Parameter::Coulomb => (-10., 10.),
+            Parameter::Speed => (0.1, 4.),
+            Parameter::Substeps => (1., 8.),
+            Parameter::CollisionCooldown => (0., 300.),
+            Parameter::RamDamage => (0., 10.),
+            Parameter::Restitution => (0., 1.),
+            Parameter::MaxObjects => (16., 4096.),
+            Parameter::FieldResolution => (4., 128.),
+            Parameter::NoCollisions
+            | Parameter::NoGravity
+            | Parameter::FreezeBodies
+            | Parameter::InvincibleShips => (0., 1.),
+        }
+    }
+
+    /// How the parameter changes per adjustment.
+    fn step(self) -> Step {
+        match self {
+            Parameter::Gravity | Parameter::Speed => Step::Scale(1.2),
+            Parameter::MaxObjects | Parameter::FieldResolution => Step::Scale(2.),
+            Parameter::Coulomb => Step::Add(0.1),
+            Parameter::Restitution => Step::Add(0.05),
+            Parameter::GravityExponent => Step::Add(0.25),
+            Parameter::CollisionCooldown => Step::Add(5.),
+            Parameter::Substeps | Parameter::RamDamage => Step::Add(1.),
+            Parameter::NoCollisions
+            | Parameter::NoGravity
+            | Parameter::FreezeBodies
+            | Parameter::InvincibleShips => Step::Toggle,
+        }
+    }
+
+    /// Returns the value one step up (for positive `direction`) or down from `value`, clamped to the allowed range.
+    /// Toggles are switched regardless of the direction.
+    pub fn adjust(self, value: f32, direction: f32) -> f32 {
+        let (min, max) = self.range();
+        match self.step() {
+            Step::Scale(factor) => value * factor.powf(direction.signum()),
+            Step::Add(amount) => value + amount * direction.signum(),
+            Step::Toggle => 1. - value,
+        }
+        .clamp(min, max)
+    }
+}
+
+/// A keyboard-navigated panel listing the adjustable simulation parameters with their current values.
+#[derive(Debug, Default)]
+pub struct TuningPanel {
+    /// The index of the currently highlighted parameter.
+    selection: usize,
+}
+
+impl TuningPanel {
+    /// Reads `PageUp` and `PageDown` to navigate the panel and `;` and `'` to adjust, and returns the selected parameter with the direction to adjust it in, if any.
+    /// None of these keys are part of a default keymap, so ships can be flown while tuning.
+    pub fn interact(&mut self) -> Option<(Parameter, f32)> {
+        let count = Parameter::ALL.len();
+        if is_key_pressed(KeyCode::PageUp) {
+            self.selection = (self.selection + count - 1) % count;
+        }
+        if is_key_pressed(KeyCode::PageDown) {
+            self.selection = (self.selection + 1) % count;
+        }
+
+        let parameter = Parameter::ALL[self.selection];
+        if is_key_pressed(KeyCode::Apostrophe) {
+            Some((parameter, 1.))
+        } else if is_key_pressed(KeyCode::Semicolon) {
+            Some((parameter, -1.))
+        } else {
+            None
+        }
+    }
+
+    /// Draws the panel in screen space, showing every parameter with the value returned by `value`.
+    pub fn draw(&self, value: impl Fn(Parameter) -> f32) {
+        // Sit above the status messages in the bottom left corner, with an extra row for the sandbox heading
+        let height = 24. + 18. * (Parameter::ALL.len() + 1) as f32;
+        let top = screen_height() - 64. - height;
+        draw_rectangle(0., top, 280., height, Color::new(0., 0., 0., 0.6));
+        draw_text(
+            "Parameters (PgUp/PgDn to select, ; ' to adjust)",
+            8.,
+            top + 16.,
+            16.,
+            WHITE,
+        );
+
+        draw_text(
+            "Sandbox",
+            8.,
+            top + 36. + 18. * Parameter::FIRST_TOGGLE as f32,
+            16.,
+            WHITE,
+        );
+        for (i, &parameter) in Parameter::ALL.iter().enumerate() {
+            let (min, max) = parameter.range();
+            let color = if i == self.selection { YELLOW } else { GRAY };
+            let (text, row) = match parameter.step() {
+                Step::Toggle => (
+                    format!(
+                        "{}: {}",
+                        parameter.name(),
+                        if value(parameter) != 0. { "on" } else { "off" }
+                    ),
+                    i + 1,
+                ),
+                _ => (
+                    format!(
+                        "{}: {:.3} [{min}, {max}]",
+                        parameter.name(),
+                        value(parameter)
+                    ),
+                    i,
+                ),
+            };
+            draw_text(&text, 8., top + 36. + 18. * row as f32, 16., color);
+        }
+    }
+}